@@ -1,4 +1,5 @@
 use anyhow::Result;
+use helper::scan;
 use std::io::{stdin, BufRead};
 
 fn main() {
@@ -14,7 +15,7 @@ fn main() {
 }
 
 fn parse_box(input: String) -> Result<Vec<usize>> {
-    input.split('x').map(|d| Ok(d.parse::<usize>()?)).collect()
+    scan::list(&input, 'x', |d| Ok(d.parse::<usize>()?))
 }
 
 fn area(b: &[usize]) -> usize {
@@ -1,14 +1,20 @@
-const START: usize = 168_630;
-const END: usize = 718_098;
+use anyhow::Result;
+use helper::scan;
 
-fn main() {
+use std::io::{stdin, Read};
+
+fn main() -> Result<()> {
+    let mut raw_input = String::new();
+    stdin().lock().read_to_string(&mut raw_input)?;
+    let (start, end) = scan!("{}-{}", usize, usize)(raw_input.trim())?;
     let mut valid: usize = 0;
-    for i in START..=END {
+    for i in start..=end {
         if is_valid(i) {
             valid += 1
         }
     }
     println!("{}", valid);
+    Ok(())
 }
 
 fn is_valid(pass: usize) -> bool {
@@ -1,53 +1,286 @@
-const START: usize = 168_630;
-const END: usize = 718_098;
+use anyhow::{anyhow, Result};
+use helper::scan;
 
-fn main() {
-    let mut valid: usize = 0;
-    for i in START..=END {
-        if is_valid(i) {
-            valid += 1
+use std::collections::HashMap;
+use std::io::{stdin, Read};
+
+// The rules for part two, which `--rules` can replace, eg.
+// `--rules digits=6,non-decreasing,has-pair` for part one
+const DEFAULT_RULES: &str = "digits=6,non-decreasing,has-exact-pair";
+
+fn main() -> Result<()> {
+    let mut raw_input = String::new();
+    stdin().lock().read_to_string(&mut raw_input)?;
+    let (start, end) = scan!("{}-{}", u64, u64)(raw_input.trim())?;
+    let rules =
+        Rules::parse(&helper::arg_value("rules").unwrap_or_else(|| DEFAULT_RULES.to_string()))?;
+    if helper::has_arg("brute") {
+        // Check every number instead, which is much slower
+        println!("{}", (start..=end).filter(|&n| rules.is_valid(n)).count());
+    } else {
+        println!("{}", rules.count(start, end));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Rule {
+    // Exactly this many digits
+    Digits(usize),
+    // Digits never decrease from left to right
+    NonDecreasing,
+    // Two adjacent digits are the same
+    HasPair,
+    // Two adjacent digits are the same and not part of a longer run
+    HasExactPair,
+}
+
+impl Rule {
+    fn parse(input: &str) -> Result<Self> {
+        match input.trim() {
+            "non-decreasing" => Ok(Rule::NonDecreasing),
+            "has-pair" => Ok(Rule::HasPair),
+            "has-exact-pair" => Ok(Rule::HasExactPair),
+            other => match scan!("digits={}", usize)(other) {
+                Ok((n,)) => Ok(Rule::Digits(n)),
+                Err(_) => Err(anyhow!("unknown rule {:?}", other)),
+            },
+        }
+    }
+
+    fn accepts(self, summary: &Summary) -> bool {
+        match self {
+            Rule::Digits(n) => summary.len == n,
+            Rule::NonDecreasing => summary.non_decreasing,
+            Rule::HasPair => summary.has_pair,
+            Rule::HasExactPair => summary.has_exact_pair || summary.run == 2,
         }
     }
-    println!("{}", valid);
 }
 
-fn is_valid(pass: usize) -> bool {
-    if pass < 100_000 || pass > 999_999 {
-        // 6 digits only
-        return false;
+/// What the rules need to know about the digits of a number, built up one
+/// digit at a time from the left.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct Summary {
+    len: usize,
+    prev: Option<u8>,
+    // The length of the run of digits the same as `prev`, only counted up to
+    // three as the rules can't tell longer runs apart
+    run: usize,
+    non_decreasing: bool,
+    has_pair: bool,
+    has_exact_pair: bool,
+}
+
+impl Default for Summary {
+    fn default() -> Self {
+        Self {
+            len: 0,
+            prev: None,
+            run: 0,
+            non_decreasing: true,
+            has_pair: false,
+            has_exact_pair: false,
+        }
     }
-    let mut last: Option<char> = None;
-    let mut consecutives: Vec<usize> = vec![];
-    let mut cur_consecutive: usize = 0;
-    for c in pass.to_string().chars() {
-        if let Some(last_c) = last {
-            if c < last_c {
-                // No decreasing
-                return false;
+}
+
+impl Summary {
+    fn of(mut n: u64) -> Self {
+        let mut digits = vec![];
+        loop {
+            digits.push((n % 10) as u8);
+            n /= 10;
+            if n == 0 {
+                break;
             }
-            if c == last_c {
-                cur_consecutive += 1;
-            } else {
-                consecutives.push(cur_consecutive);
-                cur_consecutive = 1;
+        }
+        digits
+            .into_iter()
+            .rev()
+            .fold(Summary::default(), |s, d| s.push(d))
+    }
+
+    fn push(mut self, digit: u8) -> Self {
+        match self.prev {
+            Some(prev) if prev == digit => self.run = (self.run + 1).min(3),
+            Some(prev) => {
+                if self.run == 2 {
+                    self.has_exact_pair = true;
+                }
+                if digit < prev {
+                    self.non_decreasing = false;
+                }
+                self.run = 1;
             }
-        } else {
-            cur_consecutive += 1;
+            None => self.run = 1,
+        }
+        if self.run >= 2 {
+            self.has_pair = true;
         }
-        last = Some(c);
+        self.len += 1;
+        self.prev = Some(digit);
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rules(Vec<Rule>);
+
+impl Rules {
+    fn parse(input: &str) -> Result<Self> {
+        Ok(Rules(scan::comma_list(input, Rule::parse)?))
+    }
+
+    fn is_valid(&self, pass: u64) -> bool {
+        let summary = Summary::of(pass);
+        self.0.iter().all(|r| r.accepts(&summary))
+    }
+
+    /// Counts the valid passwords in a range, inclusive.
+    fn count(&self, start: u64, end: u64) -> usize {
+        if start > end {
+            return 0;
+        }
+        self.count_to(end) - start.checked_sub(1).map(|s| self.count_to(s)).unwrap_or(0)
+    }
+
+    /// Counts the valid passwords from zero to `max` inclusive with a digit
+    /// DP, choosing digits from the left and counting every way to finish
+    /// from each summary once.
+    fn count_to(&self, max: u64) -> usize {
+        let digits: Vec<u8> = max.to_string().bytes().map(|b| b - b'0').collect();
+        let prune_decreasing = self.0.contains(&Rule::NonDecreasing);
+        let mut memo: HashMap<(usize, Summary), usize> = HashMap::new();
+
+        // `tight` is whether the digits so far match `max`, limiting the
+        // next digit, and there's no summary until the first non-zero digit
+        fn count(
+            rules: &Rules,
+            digits: &[u8],
+            prune_decreasing: bool,
+            memo: &mut HashMap<(usize, Summary), usize>,
+            pos: usize,
+            tight: bool,
+            summary: Option<Summary>,
+        ) -> usize {
+            if pos == digits.len() {
+                // Nothing but leading zeros is the number zero
+                let summary = summary.unwrap_or_else(|| Summary::default().push(0));
+                return if rules.0.iter().all(|r| r.accepts(&summary)) {
+                    1
+                } else {
+                    0
+                };
+            }
+            let key = summary.map(|s| (pos, s));
+            if let Some(found) = key.filter(|_| !tight).and_then(|k| memo.get(&k)) {
+                return *found;
+            }
+            let limit = if tight { digits[pos] } else { 9 };
+            let mut total = 0;
+            for digit in 0..=limit {
+                let next = match summary {
+                    None if digit == 0 => None,
+                    None => Some(Summary::default().push(digit)),
+                    Some(s) => Some(s.push(digit)),
+                };
+                if prune_decreasing && next.map(|s| !s.non_decreasing).unwrap_or(false) {
+                    continue;
+                }
+                total += count(
+                    rules,
+                    digits,
+                    prune_decreasing,
+                    memo,
+                    pos + 1,
+                    tight && digit == limit,
+                    next,
+                );
+            }
+            if let Some(k) = key.filter(|_| !tight) {
+                memo.insert(k, total);
+            }
+            total
+        }
+
+        count(self, &digits, prune_decreasing, &mut memo, 0, true, None)
     }
-    consecutives.push(cur_consecutive);
-    consecutives.iter().any(|&c| c == 2)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn is_valid(pass: u64) -> bool {
+        Rules::parse(DEFAULT_RULES).unwrap().is_valid(pass)
+    }
+
     #[test]
     fn test_is_valid() {
         assert_eq!(is_valid(112233), true);
         assert_eq!(is_valid(123444), false);
         assert_eq!(is_valid(111122), true);
     }
+
+    #[test]
+    fn test_rules_parse() -> Result<()> {
+        assert_eq!(
+            Rules::parse(DEFAULT_RULES)?,
+            Rules(vec![
+                Rule::Digits(6),
+                Rule::NonDecreasing,
+                Rule::HasExactPair
+            ])
+        );
+        assert!(Rules::parse("digits=6,odd").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_rules_count() -> Result<()> {
+        let part_one = Rules::parse("digits=6,non-decreasing,has-pair")?;
+        assert!(part_one.is_valid(111111));
+        assert!(!part_one.is_valid(223450));
+        assert!(!part_one.is_valid(123789));
+        assert_eq!(part_one.count(168630, 718098), 1686);
+        assert_eq!(Rules::parse(DEFAULT_RULES)?.count(168630, 718098), 1145);
+
+        // Every combination of rules agrees with checking each number
+        for rules in &[
+            "non-decreasing",
+            "has-pair",
+            "has-exact-pair",
+            "digits=3,has-pair",
+            "digits=4,non-decreasing,has-exact-pair",
+            "non-decreasing,has-pair,has-exact-pair",
+        ] {
+            let rules = Rules::parse(rules)?;
+            for &(start, end) in &[(0, 0), (0, 12000), (988, 1211), (5, 4)] {
+                assert_eq!(
+                    rules.count(start, end),
+                    (start..=end).filter(|&n| rules.is_valid(n)).count(),
+                    "{:?} from {} to {}",
+                    rules,
+                    start,
+                    end
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_rules_count_large() -> Result<()> {
+        // Non-decreasing 12 digit numbers are 12 digits chosen from 1-9 with
+        // repetition, which is C(20, 8)
+        let rules = Rules::parse("digits=12,non-decreasing")?;
+        assert_eq!(rules.count(0, 999_999_999_999), 125_970);
+        assert!(
+            Rules::parse("digits=18,non-decreasing,has-exact-pair")?
+                .count(100_000_000_000_000_000, 999_999_999_999_999_999)
+                > 0
+        );
+        Ok(())
+    }
 }
@@ -1,4 +1,5 @@
 use anyhow::{anyhow, Context, Result};
+use helper::scan;
 use std::collections::HashMap;
 use std::io::{stdin, BufRead};
 use std::iter::FromIterator;
@@ -36,20 +37,18 @@ struct Orbit {
     around: Object,
 }
 
-const ORBIT_SPLIT: char = ')';
+const ORBIT_SPLIT: &str = ")";
 
 impl Orbit {
     fn parse(input: &str) -> Result<Self> {
-        let mut parts = input.split(ORBIT_SPLIT);
-        let around = parts
-            .next()
-            .ok_or_else(|| anyhow!("unable to find around"))?
-            .to_string();
-        let object = parts.collect::<String>();
-        if object == "" {
+        let (around, object) = scan::pair(input, ORBIT_SPLIT)?;
+        if object.is_empty() {
             return Err(anyhow!("missing object"));
         }
-        Ok(Orbit { object, around })
+        Ok(Orbit {
+            object: object.to_string(),
+            around: around.to_string(),
+        })
     }
 }
 
@@ -1,48 +1,235 @@
 use anyhow::{anyhow, Context, Result};
-use std::collections::HashMap;
+use helper::scan;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::fs;
 use std::io::{stdin, BufRead};
-use std::iter::FromIterator;
 
-#[derive(Debug, PartialEq, Eq)]
-struct OrbitMap(HashMap<Object, Object>);
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum OrbitError {
+    UnknownObject(Object),
+    DuplicateOrbit(Object),
+    Cycle(Vec<Object>),
+    MultipleRoots(Vec<Object>),
+}
+
+impl fmt::Display for OrbitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrbitError::UnknownObject(name) => write!(f, "unknown object {}", name),
+            OrbitError::DuplicateOrbit(name) => write!(f, "{} orbits more than one object", name),
+            OrbitError::Cycle(names) => {
+                write!(f, "orbits form a cycle: {}", names.join(" orbits "))
+            }
+            OrbitError::MultipleRoots(names) => write!(
+                f,
+                "expected one object orbiting nothing, found {}",
+                names.join(", ")
+            ),
+        }
+    }
+}
+
+impl Error for OrbitError {}
+
+/// Orbits as a tree, with the object everything orbits at the root. Each
+/// object's ancestors at powers of two are kept so common ancestors can be
+/// found in O(log n).
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct OrbitMap {
+    names: Vec<Object>,
+    ids: HashMap<Object, usize>,
+    parents: Vec<Option<usize>>,
+    depths: Vec<usize>,
+    // `ancestors[k][id]` is the ancestor 2^k levels up, or the root
+    ancestors: Vec<Vec<usize>>,
+}
 
 impl OrbitMap {
     fn parse(input: impl BufRead) -> Result<Self> {
-        Ok(OrbitMap(HashMap::from_iter(
-            input
-                .lines()
-                .map(|l| {
-                    l.context("could not read line")
-                        .and_then(|l| Orbit::parse(&l).map(|o| (o.object, o.around)))
-                })
-                .collect::<Result<Vec<(Object, Object)>>>()?
-                .into_iter(),
-        )))
-    }
-
-    fn orbits(&self, object: &str) -> Vec<Object> {
-        match self.0.get(object) {
-            Some(parent) => {
-                let mut parent_orbits = self.orbits(parent);
-                parent_orbits.push(parent.to_owned());
-                parent_orbits
+        let orbits = input
+            .lines()
+            .map(|l| {
+                l.context("could not read line")
+                    .and_then(|l| Orbit::parse(&l))
+            })
+            .collect::<Result<Vec<Orbit>>>()?;
+        Ok(Self::new(&orbits)?)
+    }
+
+    fn new(orbits: &[Orbit]) -> Result<Self, OrbitError> {
+        let names: Vec<Object> = orbits
+            .iter()
+            .flat_map(|o| vec![o.around.to_owned(), o.object.to_owned()])
+            .collect::<BTreeSet<Object>>()
+            .into_iter()
+            .collect();
+        let ids: HashMap<Object, usize> = names
+            .iter()
+            .enumerate()
+            .map(|(id, name)| (name.to_owned(), id))
+            .collect();
+        let mut parents = vec![None; names.len()];
+        let mut children = vec![vec![]; names.len()];
+        for o in orbits {
+            let (object, around) = (ids[&o.object], ids[&o.around]);
+            if parents[object].replace(around).is_some() {
+                return Err(OrbitError::DuplicateOrbit(o.object.to_owned()));
+            }
+            children[around].push(object);
+        }
+
+        // Work out depths down from the roots, anything not reached being in
+        // a cycle
+        let roots: Vec<usize> = (0..names.len())
+            .filter(|&id| parents[id].is_none())
+            .collect();
+        let mut depths: Vec<Option<usize>> = vec![None; names.len()];
+        let mut queue: VecDeque<usize> = roots.iter().copied().collect();
+        for &root in &roots {
+            depths[root] = Some(0);
+        }
+        while let Some(id) = queue.pop_front() {
+            for &child in &children[id] {
+                depths[child] = depths[id].map(|d| d + 1);
+                queue.push_back(child);
             }
-            None => vec![],
         }
+        if let Some(start) = (0..names.len()).find(|&id| depths[id].is_none()) {
+            // Everything not reached leads into a cycle, so follow it round
+            let mut seen = vec![start];
+            let mut id = start;
+            while let Some(parent) = parents[id] {
+                if let Some(pos) = seen.iter().position(|&s| s == parent) {
+                    let mut cycle: Vec<Object> =
+                        seen[pos..].iter().map(|&s| names[s].to_owned()).collect();
+                    cycle.push(names[parent].to_owned());
+                    return Err(OrbitError::Cycle(cycle));
+                }
+                seen.push(parent);
+                id = parent;
+            }
+        }
+        if roots.len() > 1 {
+            return Err(OrbitError::MultipleRoots(
+                roots.iter().map(|&r| names[r].to_owned()).collect(),
+            ));
+        }
+
+        let depths: Vec<usize> = depths.into_iter().map(|d| d.unwrap_or(0)).collect();
+        let max_depth = depths.iter().copied().max().unwrap_or(0);
+        let mut ancestors = vec![(0..names.len())
+            .map(|id| parents[id].unwrap_or(id))
+            .collect::<Vec<usize>>()];
+        while 1 << ancestors.len() <= max_depth {
+            let last = &ancestors[ancestors.len() - 1];
+            let next = last.iter().map(|&a| last[a]).collect();
+            ancestors.push(next);
+        }
+        Ok(OrbitMap {
+            names,
+            ids,
+            parents,
+            depths,
+            ancestors,
+        })
+    }
+
+    fn contains(&self, object: &str) -> bool {
+        self.ids.contains_key(object)
+    }
+
+    fn id(&self, object: &str) -> Result<usize, OrbitError> {
+        self.ids
+            .get(object)
+            .copied()
+            .ok_or_else(|| OrbitError::UnknownObject(object.to_string()))
     }
 
-    fn orbital_transfers_between(&self, a: &str, b: &str) -> usize {
-        let a_orbits = self.orbits(a);
-        let b_orbits = self.orbits(b);
-        let shortest = std::cmp::min(a_orbits.len(), b_orbits.len());
-        let mut shared = 0;
-        for i in 0..shortest {
-            if a_orbits[i] != b_orbits[i] {
-                break;
+    /// The number of objects an object orbits, directly and indirectly.
+    fn depth(&self, object: &str) -> Result<usize, OrbitError> {
+        Ok(self.depths[self.id(object)?])
+    }
+
+    fn total_orbits(&self) -> usize {
+        self.depths.iter().sum()
+    }
+
+    fn lift(&self, mut id: usize, levels: usize) -> usize {
+        for (k, ancestors) in self.ancestors.iter().enumerate() {
+            if levels & (1 << k) != 0 {
+                id = ancestors[id];
             }
-            shared += 1;
         }
-        a_orbits.len() + b_orbits.len() - shared * 2
+        id
+    }
+
+    fn lca_id(&self, a: usize, b: usize) -> usize {
+        let (mut a, mut b) = if self.depths[a] < self.depths[b] {
+            (b, a)
+        } else {
+            (a, b)
+        };
+        a = self.lift(a, self.depths[a] - self.depths[b]);
+        if a == b {
+            return a;
+        }
+        for ancestors in self.ancestors.iter().rev() {
+            if ancestors[a] != ancestors[b] {
+                a = ancestors[a];
+                b = ancestors[b];
+            }
+        }
+        self.ancestors[0][a]
+    }
+
+    /// The lowest common ancestor, the closest object both orbit, which is
+    /// one of them if the other orbits it.
+    fn lca(&self, a: &str, b: &str) -> Result<&str, OrbitError> {
+        Ok(&self.names[self.lca_id(self.id(a)?, self.id(b)?)])
+    }
+
+    /// The objects on the way from `a` to `b`, including both ends.
+    fn path(&self, a: &str, b: &str) -> Result<Vec<&str>, OrbitError> {
+        let (a, b) = (self.id(a)?, self.id(b)?);
+        let lca = self.lca_id(a, b);
+        let up = |mut id: usize| {
+            let mut path = vec![];
+            while id != lca {
+                path.push(self.names[id].as_str());
+                id = self.ancestors[0][id];
+            }
+            path
+        };
+        let mut path = up(a);
+        path.push(&self.names[lca]);
+        path.extend(up(b).into_iter().rev());
+        Ok(path)
+    }
+
+    /// The orbital transfers needed to move from the object `a` orbits to the
+    /// object `b` orbits.
+    fn orbital_transfers_between(&self, a: &str, b: &str) -> Result<usize, OrbitError> {
+        let (a, b) = (self.id(a)?, self.id(b)?);
+        let lca = self.lca_id(a, b);
+        Ok((self.depths[a] + self.depths[b] - 2 * self.depths[lca]).saturating_sub(2))
+    }
+
+    /// The map in Graphviz DOT format, with an edge from each object to what
+    /// it orbits.
+    fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph orbits {\n    rankdir=BT;\n");
+        for (id, parent) in self.parents.iter().enumerate() {
+            if let Some(p) = parent {
+                dot.push_str(&format!(
+                    "    \"{}\" -> \"{}\";\n",
+                    self.names[id], self.names[*p]
+                ));
+            }
+        }
+        dot.push_str("}\n");
+        dot
     }
 }
 
@@ -54,26 +241,41 @@ struct Orbit {
     around: Object,
 }
 
-const ORBIT_SPLIT: char = ')';
+const ORBIT_SPLIT: &str = ")";
 
 impl Orbit {
     fn parse(input: &str) -> Result<Self> {
-        let mut parts = input.split(ORBIT_SPLIT);
-        let around = parts
-            .next()
-            .ok_or_else(|| anyhow!("unable to find around"))?
-            .to_string();
-        let object = parts.collect::<String>();
-        if object == "" {
+        let (around, object) = scan::pair(input, ORBIT_SPLIT)?;
+        if object.is_empty() {
             return Err(anyhow!("missing object"));
         }
-        Ok(Orbit { object, around })
+        Ok(Orbit {
+            object: object.to_string(),
+            around: around.to_string(),
+        })
     }
 }
 
 fn main() -> Result<()> {
     let om = OrbitMap::parse(stdin().lock())?;
-    println!("{}", om.orbital_transfers_between("SAN", "YOU"));
+    // Optionally draw the map, eg. `day06b --dot orbits.dot < res/input`
+    if let Some(path) = helper::arg_value("dot") {
+        fs::write(&path, om.to_dot()).with_context(|| format!("failed writing {}", path))?;
+    }
+    if helper::has_arg("total") {
+        println!("{} orbits in total", om.total_orbits());
+    }
+    if helper::has_arg("path") {
+        let lca = om.lca("YOU", "SAN")?;
+        println!("{}", om.path("YOU", "SAN")?.join(" -> "));
+        println!("Meeting at {}, {} orbits deep", lca, om.depth(lca)?);
+    }
+    // Maps without both of us, like the part one examples, need no transfers
+    if om.contains("SAN") && om.contains("YOU") {
+        println!("{}", om.orbital_transfers_between("SAN", "YOU")?);
+    } else {
+        println!("0");
+    }
     Ok(())
 }
 
@@ -102,15 +304,57 @@ mod tests {
     fn test_orbit_parse_map() -> Result<()> {
         assert!(OrbitMap::parse("fart".as_bytes()).is_err());
         let om = OrbitMap::parse(TEST_INPUT.as_bytes())?;
-        assert_eq!(om.0.get("B"), Some(&"COM".to_string()));
-        assert_eq!(om.0.get("L"), Some(&"K".to_string()));
+        assert_eq!(om.path("B", "COM")?, vec!["B", "COM"]);
+        assert_eq!(om.path("L", "K")?, vec!["L", "K"]);
+        assert_eq!(om.depth("COM")?, 0);
         Ok(())
     }
 
     #[test]
     fn test_orbit_map_orbital_transfers_between() -> Result<()> {
         let om = OrbitMap::parse(TEST_INPUT_B.as_bytes())?;
-        assert_eq!(om.orbital_transfers_between("SAN", "YOU"), 4);
+        assert_eq!(om.orbital_transfers_between("SAN", "YOU")?, 4);
+        assert!(om.contains("SAN"));
+        assert!(!om.contains("ZZZ"));
+        assert_eq!(
+            om.orbital_transfers_between("SAN", "ZZZ"),
+            Err(OrbitError::UnknownObject("ZZZ".to_string()))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_orbit_map_tree() -> Result<()> {
+        let om = OrbitMap::parse(TEST_INPUT.as_bytes())?;
+        assert_eq!(om.total_orbits(), 42);
+        assert_eq!(om.depth("D")?, 3);
+        assert_eq!(om.depth("L")?, 7);
+        assert_eq!(om.depth("COM")?, 0);
+        assert_eq!(om.lca("L", "H")?, "B");
+        assert_eq!(om.lca("L", "I")?, "D");
+        assert_eq!(om.lca("F", "L")?, "E");
+        assert_eq!(om.lca("E", "L")?, "E");
+        assert_eq!(om.lca("COM", "COM")?, "COM");
+        assert_eq!(
+            om.path("L", "H")?,
+            vec!["L", "K", "J", "E", "D", "C", "B", "G", "H"]
+        );
+        assert_eq!(om.path("C", "C")?, vec!["C"]);
+        assert!(om.to_dot().contains("    \"B\" -> \"COM\";\n"));
         Ok(())
     }
+
+    #[test]
+    fn test_orbit_map_errors() {
+        let parse = |input: &str| OrbitMap::parse(input.as_bytes()).unwrap_err().to_string();
+        assert_eq!(
+            parse("COM)A\nA)B\nC)D\nD)E\nE)C"),
+            "orbits form a cycle: C orbits E orbits D orbits C"
+        );
+        assert_eq!(
+            parse("COM)A\nX)Y"),
+            "expected one object orbiting nothing, found COM, X"
+        );
+        assert_eq!(parse("COM)A\nB)A"), "A orbits more than one object");
+    }
 }
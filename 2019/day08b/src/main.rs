@@ -1,71 +1,53 @@
-use anyhow::{anyhow, Result};
-use std::io::{stdin, BufRead};
+use anyhow::Result;
+use helper::ocr;
+use shared::sif::Sif;
+use std::io::{stdin, Read};
 
-#[derive(Debug)]
-struct SpaceImage<'a> {
-    data: &'a str,
-    width: usize,
-    height: usize,
+/// Renders an image, along with its letters if there's a font its size.
+fn read(image: &Sif) -> Result<(String, Option<String>)> {
+    let rendered = image.render();
+    let letters = if ocr::has_font(image.height()) {
+        Some(ocr::recognise(&rendered)?)
+    } else {
+        None
+    };
+    Ok((rendered, letters))
 }
 
-impl<'a> SpaceImage<'a> {
-    fn layer_size(&self) -> usize {
-        self.width * self.height
-    }
-
-    fn layers(&self) -> Vec<SpaceImageLayer<'a>> {
-        let layer_size = self.layer_size();
-        (0..self.data.len())
-            .step_by(self.width * self.height)
-            .map(|start| SpaceImageLayer {
-                data: &self.data[start..start + layer_size],
-                width: self.width,
-                height: self.height,
-            })
-            .collect()
+fn main() -> Result<()> {
+    let mut raw = String::new();
+    stdin().read_to_string(&mut raw)?;
+    let image = Sif::decode(
+        &raw,
+        helper::arg_parse("width", 25)?,
+        helper::arg_parse("height", 6)?,
+    )?;
+    // Optionally write the image out, eg. `day08b --image image.png < res/input`
+    if let Some(path) = helper::arg_value("image") {
+        image.to_image(10).save(path)?;
     }
-
-    fn render(&self) -> String {
-        let layers = self.layers();
-        if layers.is_empty() {
-            return "".to_string();
-        }
-        let mut output = layers[0].data.as_bytes().to_owned();
-        for l in layers.into_iter().skip(1) {
-            for (index, c) in l.data.as_bytes().iter().enumerate() {
-                if output[index] == b'2' {
-                    output[index] = *c;
-                }
-            }
-        }
-        (0..output.len())
-            .step_by(self.width)
-            .map(|start| String::from_utf8(output[start..start + self.width].to_owned()).unwrap())
-            .collect::<Vec<String>>()
-            .join("\n")
-            .replace("0", " ")
-            .replace("1", "#")
+    let (rendered, letters) = read(&image)?;
+    println!("{}", rendered);
+    if let Some(letters) = letters {
+        println!("{}", letters);
     }
+    Ok(())
 }
 
-#[derive(Debug)]
-struct SpaceImageLayer<'a> {
-    data: &'a str,
-    width: usize,
-    height: usize,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-fn main() -> Result<()> {
-    let raw: String = stdin()
-        .lock()
-        .lines()
-        .next()
-        .ok_or_else(|| anyhow!("expected a line"))??;
-    let image = SpaceImage {
-        data: &raw,
-        width: 25,
-        height: 6,
-    };
-    println!("{}", image.render());
-    Ok(())
+    #[test]
+    fn test_read() -> Result<()> {
+        // Sizes without a font are still rendered
+        let image = Sif::decode("0222112222120000", 2, 2)?;
+        let (rendered, letters) = read(&image)?;
+        assert_eq!(rendered, " #\n# ");
+        assert_eq!(letters, None);
+
+        let image = Sif::decode(include_str!("../res/input").trim(), 25, 6)?;
+        assert_eq!(read(&image)?.1, Some("LRFKU".to_string()));
+        Ok(())
+    }
 }
@@ -1,10 +1,9 @@
 use anyhow::{anyhow, Result};
-use num;
 
-use std::collections::{HashMap, HashSet};
+use helper::point::IPoint2;
+
+use std::collections::HashMap;
 use std::io::{stdin, Read};
-use std::iter::FromIterator;
-use std::ops::{Add, Sub};
 
 #[derive(Debug, PartialEq, Eq)]
 enum MapItem {
@@ -43,7 +42,7 @@ impl Map {
         input.chars().map(MapItem::from_char).collect()
     }
 
-    fn find(&self, kind: &MapItem) -> Vec<Point> {
+    fn find(&self, kind: &MapItem) -> Vec<IPoint2> {
         self.0
             .iter()
             .enumerate()
@@ -52,7 +51,7 @@ impl Map {
                     .enumerate()
                     .filter_map(|(x, item)| {
                         if item == kind {
-                            Some(Point {
+                            Some(IPoint2 {
                                 x: x as isize,
                                 y: y as isize,
                             })
@@ -60,35 +59,28 @@ impl Map {
                             None
                         }
                     })
-                    .collect::<Vec<Point>>()
+                    .collect::<Vec<IPoint2>>()
             })
             .flatten()
             .collect()
     }
 
-    fn visible_pairs(&self) -> Vec<[Point; 2]> {
+    fn visible_pairs(&self) -> Vec<[IPoint2; 2]> {
         let asteroids = self.find(&MapItem::Asteroid);
-        let asteroids_set: HashSet<Point> = HashSet::from_iter(asteroids.to_owned());
-        let mut pairs: Vec<[Point; 2]> = vec![];
-        for (index, a1) in asteroids.iter().enumerate().take(asteroids.len() - 1) {
-            'a2: for a2 in asteroids.iter().skip(index + 1) {
-                let step = (*a2 - *a1).smallest_whole_vector();
-                let mut ptr = *a1 + step;
-                while ptr != *a2 {
-                    if asteroids_set.contains(&ptr) {
-                        // There is an asteroid in the way, skip this pair
-                        continue 'a2;
-                    }
-                    ptr = ptr + step;
+        let mut pairs: Vec<[IPoint2; 2]> = vec![];
+        for (index, a1) in asteroids.iter().enumerate() {
+            let nearest = nearest_by_direction(&asteroids, *a1);
+            for a2 in asteroids.iter().skip(index + 1) {
+                if nearest.get(&(*a2 - *a1).direction()) == Some(a2) {
+                    pairs.push([*a1, *a2]);
                 }
-                pairs.push([*a1, *a2]);
             }
         }
         pairs
     }
 
-    fn most_visible(&self) -> (Vec<Point>, usize) {
-        let mut asteroid_visible: HashMap<Point, usize> = HashMap::new();
+    fn most_visible(&self) -> (Vec<IPoint2>, usize) {
+        let mut asteroid_visible: HashMap<IPoint2, usize> = HashMap::new();
         let mut most = 0;
         for pair in self.visible_pairs() {
             for a in pair.iter() {
@@ -109,55 +101,21 @@ impl Map {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
-struct Point {
-    x: isize,
-    y: isize,
-}
-
-impl Sub for Point {
-    type Output = Self;
-
-    fn sub(self, other: Point) -> Self {
-        Point {
-            x: self.x - other.x,
-            y: self.y - other.y,
-        }
-    }
-}
-
-impl Add for Point {
-    type Output = Self;
-
-    fn add(self, other: Self) -> Self {
-        Self {
-            x: self.x + other.x,
-            y: self.y + other.y,
+/// Buckets asteroids by their direction from `from`, reduced so asteroids
+/// lined up share one, keeping the nearest in each as it's the only one
+/// visible.
+fn nearest_by_direction(asteroids: &[IPoint2], from: IPoint2) -> HashMap<IPoint2, IPoint2> {
+    let mut nearest: HashMap<IPoint2, IPoint2> = HashMap::new();
+    for &a in asteroids {
+        if a == from {
+            continue;
         }
-    }
-}
-
-impl Point {
-    fn smallest_whole_vector(self) -> Point {
-        match self {
-            Point { x: 0, y: 0 } => self,
-            Point { x, y: 0 } => Point {
-                x: x.signum(),
-                y: 0,
-            },
-            Point { x: 0, y } => Point {
-                x: 0,
-                y: y.signum(),
-            },
-            Point { x, y } => {
-                let gcd = num::integer::gcd(x, y);
-                Point {
-                    x: x / gcd,
-                    y: y / gcd,
-                }
-            }
+        let nearer = nearest.entry((a - from).direction()).or_insert(a);
+        if (a - from).manhattan() < (*nearer - from).manhattan() {
+            *nearer = a;
         }
     }
+    nearest
 }
 
 fn main() -> Result<()> {
@@ -265,10 +223,10 @@ mod tests {
         assert_eq!(
             map.find(&MapItem::Asteroid),
             vec![
-                Point { x: 1, y: 0 },
-                Point { x: 0, y: 2 },
-                Point { x: 1, y: 2 },
-                Point { x: 2, y: 2 },
+                IPoint2 { x: 1, y: 0 },
+                IPoint2 { x: 0, y: 2 },
+                IPoint2 { x: 1, y: 2 },
+                IPoint2 { x: 2, y: 2 },
             ]
         );
         Ok(())
@@ -286,18 +244,18 @@ mod tests {
         assert_eq!(
             map.visible_pairs(),
             vec![
-                [Point { x: 0, y: 0 }, Point { x: 1, y: 0 }],
-                [Point { x: 0, y: 0 }, Point { x: 1, y: 1 }],
-                [Point { x: 0, y: 0 }, Point { x: 0, y: 2 }],
-                [Point { x: 0, y: 0 }, Point { x: 1, y: 2 }],
-                [Point { x: 1, y: 0 }, Point { x: 1, y: 1 }],
-                [Point { x: 1, y: 0 }, Point { x: 0, y: 2 }],
-                [Point { x: 1, y: 0 }, Point { x: 2, y: 2 }],
-                [Point { x: 1, y: 1 }, Point { x: 0, y: 2 }],
-                [Point { x: 1, y: 1 }, Point { x: 1, y: 2 }],
-                [Point { x: 1, y: 1 }, Point { x: 2, y: 2 }],
-                [Point { x: 0, y: 2 }, Point { x: 1, y: 2 }],
-                [Point { x: 1, y: 2 }, Point { x: 2, y: 2 }],
+                [IPoint2 { x: 0, y: 0 }, IPoint2 { x: 1, y: 0 }],
+                [IPoint2 { x: 0, y: 0 }, IPoint2 { x: 1, y: 1 }],
+                [IPoint2 { x: 0, y: 0 }, IPoint2 { x: 0, y: 2 }],
+                [IPoint2 { x: 0, y: 0 }, IPoint2 { x: 1, y: 2 }],
+                [IPoint2 { x: 1, y: 0 }, IPoint2 { x: 1, y: 1 }],
+                [IPoint2 { x: 1, y: 0 }, IPoint2 { x: 0, y: 2 }],
+                [IPoint2 { x: 1, y: 0 }, IPoint2 { x: 2, y: 2 }],
+                [IPoint2 { x: 1, y: 1 }, IPoint2 { x: 0, y: 2 }],
+                [IPoint2 { x: 1, y: 1 }, IPoint2 { x: 1, y: 2 }],
+                [IPoint2 { x: 1, y: 1 }, IPoint2 { x: 2, y: 2 }],
+                [IPoint2 { x: 0, y: 2 }, IPoint2 { x: 1, y: 2 }],
+                [IPoint2 { x: 1, y: 2 }, IPoint2 { x: 2, y: 2 }],
             ]
         );
         Ok(())
@@ -305,37 +263,16 @@ mod tests {
 
     #[test]
     fn test_point_smallest_whole_vector() {
+        assert_eq!(IPoint2 { x: 0, y: 0 }.direction(), IPoint2 { x: 0, y: 0 });
+        assert_eq!(IPoint2 { x: 0, y: 5 }.direction(), IPoint2 { x: 0, y: 1 });
+        assert_eq!(IPoint2 { x: 0, y: -5 }.direction(), IPoint2 { x: 0, y: -1 });
+        assert_eq!(IPoint2 { x: 5, y: 0 }.direction(), IPoint2 { x: 1, y: 0 });
+        assert_eq!(IPoint2 { x: -5, y: 0 }.direction(), IPoint2 { x: -1, y: 0 });
+        assert_eq!(IPoint2 { x: 3, y: 3 }.direction(), IPoint2 { x: 1, y: 1 });
+        assert_eq!(IPoint2 { x: 4, y: 6 }.direction(), IPoint2 { x: 2, y: 3 });
         assert_eq!(
-            Point { x: 0, y: 0 }.smallest_whole_vector(),
-            Point { x: 0, y: 0 }
-        );
-        assert_eq!(
-            Point { x: 0, y: 5 }.smallest_whole_vector(),
-            Point { x: 0, y: 1 }
-        );
-        assert_eq!(
-            Point { x: 0, y: -5 }.smallest_whole_vector(),
-            Point { x: 0, y: -1 }
-        );
-        assert_eq!(
-            Point { x: 5, y: 0 }.smallest_whole_vector(),
-            Point { x: 1, y: 0 }
-        );
-        assert_eq!(
-            Point { x: -5, y: 0 }.smallest_whole_vector(),
-            Point { x: -1, y: 0 }
-        );
-        assert_eq!(
-            Point { x: 3, y: 3 }.smallest_whole_vector(),
-            Point { x: 1, y: 1 }
-        );
-        assert_eq!(
-            Point { x: 4, y: 6 }.smallest_whole_vector(),
-            Point { x: 2, y: 3 }
-        );
-        assert_eq!(
-            Point { x: 7, y: -10 }.smallest_whole_vector(),
-            Point { x: 7, y: -10 }
+            IPoint2 { x: 7, y: -10 }.direction(),
+            IPoint2 { x: 7, y: -10 }
         );
     }
 
@@ -350,7 +287,7 @@ mod tests {
 ...##
 ",
         )?;
-        assert_eq!(map.most_visible(), (vec![Point { x: 3, y: 4 }], 8));
+        assert_eq!(map.most_visible(), (vec![IPoint2 { x: 3, y: 4 }], 8));
 
         map = Map::parse(
             "
@@ -366,7 +303,7 @@ mod tests {
 .#....####
 ",
         )?;
-        assert_eq!(map.most_visible(), (vec![Point { x: 5, y: 8 }], 33));
+        assert_eq!(map.most_visible(), (vec![IPoint2 { x: 5, y: 8 }], 33));
 
         map = Map::parse(
             "
@@ -382,7 +319,7 @@ mod tests {
 .####.###.
 ",
         )?;
-        assert_eq!(map.most_visible(), (vec![Point { x: 1, y: 2 }], 35));
+        assert_eq!(map.most_visible(), (vec![IPoint2 { x: 1, y: 2 }], 35));
 
         map = Map::parse(
             "
@@ -398,7 +335,7 @@ mod tests {
 .....#.#..
 ",
         )?;
-        assert_eq!(map.most_visible(), (vec![Point { x: 6, y: 3 }], 41));
+        assert_eq!(map.most_visible(), (vec![IPoint2 { x: 6, y: 3 }], 41));
 
         map = Map::parse(
             "
@@ -424,7 +361,7 @@ mod tests {
 ###.##.####.##.#..##
 ",
         )?;
-        assert_eq!(map.most_visible(), (vec![Point { x: 11, y: 13 }], 210));
+        assert_eq!(map.most_visible(), (vec![IPoint2 { x: 11, y: 13 }], 210));
 
         Ok(())
     }
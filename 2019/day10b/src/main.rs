@@ -1,11 +1,12 @@
 use anyhow::{anyhow, Result};
-use num;
 
-use std::collections::{HashMap, HashSet};
-use std::f64::consts::{FRAC_PI_2, PI};
+use helper::grid::Grid;
+use helper::point::IPoint2;
+use helper::scan;
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, VecDeque};
 use std::io::{stdin, Read};
-use std::iter::FromIterator;
-use std::ops::{Add, Sub};
 
 #[derive(Debug, PartialEq, Eq)]
 enum MapItem {
@@ -27,69 +28,36 @@ impl MapItem {
 }
 
 #[derive(Debug, PartialEq, Eq)]
-struct Map(Vec<Vec<MapItem>>);
+struct Map(Grid<MapItem>);
 
 impl Map {
     fn parse(input: &str) -> Result<Self> {
-        Ok(Self(
-            input
-                .trim()
-                .lines()
-                .map(Map::parse_line)
-                .collect::<Result<Vec<Vec<MapItem>>>>()?,
-        ))
-    }
-
-    fn parse_line(input: &str) -> Result<Vec<MapItem>> {
-        input.chars().map(MapItem::from_char).collect()
+        Ok(Self(Grid::parse(input.trim(), MapItem::from_char)?))
     }
 
-    fn find(&self, kind: &MapItem) -> Vec<Point> {
+    fn find(&self, kind: &MapItem) -> Vec<IPoint2> {
         self.0
             .iter()
-            .enumerate()
-            .map(|(y, row)| {
-                row.iter()
-                    .enumerate()
-                    .filter_map(|(x, item)| {
-                        if item == kind {
-                            Some(Point {
-                                x: x as isize,
-                                y: y as isize,
-                            })
-                        } else {
-                            None
-                        }
-                    })
-                    .collect::<Vec<Point>>()
-            })
-            .flatten()
+            .filter_map(|(p, item)| if item == kind { Some(p) } else { None })
             .collect()
     }
 
-    fn visible_pairs(&self) -> Vec<[Point; 2]> {
+    fn visible_pairs(&self) -> Vec<[IPoint2; 2]> {
         let asteroids = self.find(&MapItem::Asteroid);
-        let asteroids_set: HashSet<Point> = HashSet::from_iter(asteroids.to_owned());
-        let mut pairs: Vec<[Point; 2]> = vec![];
-        for (index, a1) in asteroids.iter().enumerate().take(asteroids.len() - 1) {
-            'a2: for a2 in asteroids.iter().skip(index + 1) {
-                let step = (*a2 - *a1).smallest_whole_vector();
-                let mut ptr = *a1 + step;
-                while ptr != *a2 {
-                    if asteroids_set.contains(&ptr) {
-                        // There is an asteroid in the way, skip this pair
-                        continue 'a2;
-                    }
-                    ptr = ptr + step;
+        let mut pairs: Vec<[IPoint2; 2]> = vec![];
+        for (index, a1) in asteroids.iter().enumerate() {
+            let nearest = nearest_by_direction(&asteroids, *a1);
+            for a2 in asteroids.iter().skip(index + 1) {
+                if nearest.get(&(*a2 - *a1).direction()) == Some(a2) {
+                    pairs.push([*a1, *a2]);
                 }
-                pairs.push([*a1, *a2]);
             }
         }
         pairs
     }
 
-    fn most_visible(&self) -> (Vec<Point>, usize) {
-        let mut asteroid_visible: HashMap<Point, usize> = HashMap::new();
+    fn most_visible(&self) -> (Vec<IPoint2>, usize) {
+        let mut asteroid_visible: HashMap<IPoint2, usize> = HashMap::new();
         let mut most = 0;
         for pair in self.visible_pairs() {
             for a in pair.iter() {
@@ -109,114 +77,125 @@ impl Map {
         )
     }
 
-    fn laser_targets(&self, from: Point) -> Vec<Point> {
-        // Find the angles of all other points
-        let mut asteroids: Vec<RelativeAsteroid> = self
-            .find(&MapItem::Asteroid)
-            .into_iter()
-            .filter_map(|a| {
-                if a != from {
-                    let diff = a - from;
-                    let angle_diff = diff.smallest_whole_vector();
-                    Some(RelativeAsteroid {
-                        at: a,
-                        angle: (-FRAC_PI_2 - (angle_diff.y as f64).atan2(-angle_diff.x as f64)
-                            + 2f64 * PI)
-                            % (2f64 * PI),
-                        dist: a.length(),
-                    })
-                } else {
-                    None
-                }
-            })
-            .collect();
-        asteroids.sort_by(|a, b| {
-            a.angle
-                .partial_cmp(&b.angle)
-                .unwrap()
-                .then(a.dist.partial_cmp(&b.dist).unwrap())
-        });
-        let mut targets: Vec<Point> = vec![];
-        let mut index = 0;
-        let mut last_angle: Option<f64> = None;
-        loop {
-            if Some(asteroids[index].angle) != last_angle {
-                last_angle = Some(asteroids[index].angle);
-                targets.push(asteroids[index].at);
-                asteroids.remove(index);
-                // We don't increment index as we removed the current item anyway
-                if asteroids.is_empty() {
-                    break;
-                }
-            } else {
-                index += 1;
+    /// The asteroids a laser at `from` vaporizes, in order.
+    fn laser_targets(&self, from: IPoint2, laser: Laser) -> Vaporize {
+        let mut by_direction: HashMap<IPoint2, Vec<IPoint2>> = HashMap::new();
+        for a in self.find(&MapItem::Asteroid) {
+            if a != from {
+                by_direction
+                    .entry((a - from).direction())
+                    .or_default()
+                    .push(a);
             }
-            index %= asteroids.len();
         }
-        targets
+        let mut directions: Vec<(IPoint2, Vec<IPoint2>)> = by_direction.into_iter().collect();
+        directions.sort_by(|(a, _), (b, _)| laser.cmp_angle(*a, *b));
+        Vaporize {
+            directions: directions
+                .into_iter()
+                .map(|(_, mut asteroids)| {
+                    asteroids.sort_by_key(|a| (*a - from).manhattan());
+                    asteroids.into_iter().collect()
+                })
+                .collect(),
+        }
     }
 }
 
-#[derive(Debug)]
-struct RelativeAsteroid {
-    at: Point,
-    angle: f64,
-    dist: f64,
+/// Which way a laser points to start with and which way it turns.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Laser {
+    start: IPoint2,
+    clockwise: bool,
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
-struct Point {
-    x: isize,
-    y: isize,
+impl Default for Laser {
+    /// Starting pointing up and turning clockwise.
+    fn default() -> Self {
+        Self {
+            start: IPoint2 { x: 0, y: -1 },
+            clockwise: true,
+        }
+    }
 }
 
-impl Sub for Point {
-    type Output = Self;
+impl Laser {
+    /// Starts pointing in a direction, which must be non-zero.
+    fn start(mut self, start: IPoint2) -> Self {
+        self.start = start.direction();
+        self
+    }
 
-    fn sub(self, other: Point) -> Self {
-        Point {
-            x: self.x - other.x,
-            y: self.y - other.y,
+    fn anticlockwise(mut self) -> Self {
+        self.clockwise = false;
+        self
+    }
+
+    // The cross product in the direction of turning, which is positive when
+    // `b` is less than half a turn past `a`. With y pointing down, clockwise
+    // is positive.
+    fn turn(&self, a: IPoint2, b: IPoint2) -> isize {
+        if self.clockwise {
+            a.cross(b)
+        } else {
+            -a.cross(b)
         }
     }
+
+    /// Orders directions by how far the laser turns to reach them, exactly
+    /// rather than comparing floating point angles.
+    fn cmp_angle(&self, a: IPoint2, b: IPoint2) -> Ordering {
+        // Directions in the first half of the turn, including the start, come
+        // before those in the second
+        let half = |d: IPoint2| {
+            let turn = self.turn(self.start, d);
+            if turn > 0 || (turn == 0 && self.start.dot(d) > 0) {
+                0
+            } else {
+                1
+            }
+        };
+        half(a).cmp(&half(b)).then_with(|| 0.cmp(&self.turn(a, b)))
+    }
 }
 
-impl Add for Point {
-    type Output = Self;
+/// Asteroids in the order a laser vaporizes them, the nearest in each
+/// direction on each turn.
+#[derive(Debug, Clone)]
+struct Vaporize {
+    // Directions still with asteroids in, in the order the laser reaches
+    // them, each with its asteroids nearest first
+    directions: VecDeque<VecDeque<IPoint2>>,
+}
 
-    fn add(self, other: Self) -> Self {
-        Self {
-            x: self.x + other.x,
-            y: self.y + other.y,
+impl Iterator for Vaporize {
+    type Item = IPoint2;
+
+    fn next(&mut self) -> Option<IPoint2> {
+        let mut direction = self.directions.pop_front()?;
+        let target = direction.pop_front();
+        if !direction.is_empty() {
+            self.directions.push_back(direction);
         }
+        target
     }
 }
 
-impl Point {
-    fn smallest_whole_vector(self) -> Point {
-        match self {
-            Point { x: 0, y: 0 } => self,
-            Point { x, y: 0 } => Point {
-                x: x.signum(),
-                y: 0,
-            },
-            Point { x: 0, y } => Point {
-                x: 0,
-                y: y.signum(),
-            },
-            Point { x, y } => {
-                let gcd = num::integer::gcd(x, y);
-                Point {
-                    x: x / gcd,
-                    y: y / gcd,
-                }
-            }
+/// Buckets asteroids by their direction from `from`, reduced so asteroids
+/// lined up share one, keeping the nearest in each as it's the only one
+/// visible.
+fn nearest_by_direction(asteroids: &[IPoint2], from: IPoint2) -> HashMap<IPoint2, IPoint2> {
+    let mut nearest: HashMap<IPoint2, IPoint2> = HashMap::new();
+    for &a in asteroids {
+        if a == from {
+            continue;
+        }
+        let nearer = nearest.entry((a - from).direction()).or_insert(a);
+        if (a - from).manhattan() < (*nearer - from).manhattan() {
+            *nearer = a;
         }
     }
-
-    fn length(&self) -> f64 {
-        ((self.x.pow(2) + self.y.pow(2)) as f64).sqrt()
-    }
+    nearest
 }
 
 fn main() -> Result<()> {
@@ -228,7 +207,25 @@ fn main() -> Result<()> {
         .into_iter()
         .next()
         .ok_or_else(|| anyhow!("could not find asteroid"))?;
-    println!("{:?}", map.laser_targets(asteroid).get(199));
+    // Optionally turn the other way or start elsewhere, eg. `--anticlockwise
+    // --start 1,0 --nth 10`
+    let mut laser = Laser::default();
+    if let Some(start) = helper::arg_value("start") {
+        let (x, y) = scan!("{},{}", isize, isize)(&start)?;
+        if x == 0 && y == 0 {
+            return Err(anyhow!("laser must start pointing somewhere"));
+        }
+        laser = laser.start(IPoint2 { x, y });
+    }
+    if helper::has_arg("anticlockwise") {
+        laser = laser.anticlockwise();
+    }
+    let nth: usize = helper::arg_parse("nth", 200)?;
+    let target = map
+        .laser_targets(asteroid, laser)
+        .nth(nth.saturating_sub(1))
+        .ok_or_else(|| anyhow!("fewer than {} asteroids to vaporize", nth))?;
+    println!("{}", target.x * 100 + target.y);
     Ok(())
 }
 
@@ -251,14 +248,16 @@ mod tests {
     #[test]
     fn test_map_parse_line() -> Result<()> {
         assert_eq!(
-            Map::parse_line(".#..#")?,
-            vec![
-                MapItem::Empty,
-                MapItem::Asteroid,
-                MapItem::Empty,
-                MapItem::Empty,
-                MapItem::Asteroid,
-            ]
+            Map::parse(".#..#")?.0.row(0),
+            Some(
+                &[
+                    MapItem::Empty,
+                    MapItem::Asteroid,
+                    MapItem::Empty,
+                    MapItem::Empty,
+                    MapItem::Asteroid,
+                ][..]
+            )
         );
         Ok(())
     }
@@ -275,7 +274,7 @@ mod tests {
 ...##
 "
             )?,
-            Map(vec![
+            Map(Grid::from_rows(vec![
                 vec![
                     MapItem::Empty,
                     MapItem::Asteroid,
@@ -311,7 +310,7 @@ mod tests {
                     MapItem::Asteroid,
                     MapItem::Asteroid,
                 ],
-            ]),
+            ])?),
         );
         Ok(())
     }
@@ -328,10 +327,10 @@ mod tests {
         assert_eq!(
             map.find(&MapItem::Asteroid),
             vec![
-                Point { x: 1, y: 0 },
-                Point { x: 0, y: 2 },
-                Point { x: 1, y: 2 },
-                Point { x: 2, y: 2 },
+                IPoint2 { x: 1, y: 0 },
+                IPoint2 { x: 0, y: 2 },
+                IPoint2 { x: 1, y: 2 },
+                IPoint2 { x: 2, y: 2 },
             ]
         );
         Ok(())
@@ -349,18 +348,18 @@ mod tests {
         assert_eq!(
             map.visible_pairs(),
             vec![
-                [Point { x: 0, y: 0 }, Point { x: 1, y: 0 }],
-                [Point { x: 0, y: 0 }, Point { x: 1, y: 1 }],
-                [Point { x: 0, y: 0 }, Point { x: 0, y: 2 }],
-                [Point { x: 0, y: 0 }, Point { x: 1, y: 2 }],
-                [Point { x: 1, y: 0 }, Point { x: 1, y: 1 }],
-                [Point { x: 1, y: 0 }, Point { x: 0, y: 2 }],
-                [Point { x: 1, y: 0 }, Point { x: 2, y: 2 }],
-                [Point { x: 1, y: 1 }, Point { x: 0, y: 2 }],
-                [Point { x: 1, y: 1 }, Point { x: 1, y: 2 }],
-                [Point { x: 1, y: 1 }, Point { x: 2, y: 2 }],
-                [Point { x: 0, y: 2 }, Point { x: 1, y: 2 }],
-                [Point { x: 1, y: 2 }, Point { x: 2, y: 2 }],
+                [IPoint2 { x: 0, y: 0 }, IPoint2 { x: 1, y: 0 }],
+                [IPoint2 { x: 0, y: 0 }, IPoint2 { x: 1, y: 1 }],
+                [IPoint2 { x: 0, y: 0 }, IPoint2 { x: 0, y: 2 }],
+                [IPoint2 { x: 0, y: 0 }, IPoint2 { x: 1, y: 2 }],
+                [IPoint2 { x: 1, y: 0 }, IPoint2 { x: 1, y: 1 }],
+                [IPoint2 { x: 1, y: 0 }, IPoint2 { x: 0, y: 2 }],
+                [IPoint2 { x: 1, y: 0 }, IPoint2 { x: 2, y: 2 }],
+                [IPoint2 { x: 1, y: 1 }, IPoint2 { x: 0, y: 2 }],
+                [IPoint2 { x: 1, y: 1 }, IPoint2 { x: 1, y: 2 }],
+                [IPoint2 { x: 1, y: 1 }, IPoint2 { x: 2, y: 2 }],
+                [IPoint2 { x: 0, y: 2 }, IPoint2 { x: 1, y: 2 }],
+                [IPoint2 { x: 1, y: 2 }, IPoint2 { x: 2, y: 2 }],
             ]
         );
         Ok(())
@@ -368,37 +367,16 @@ mod tests {
 
     #[test]
     fn test_point_smallest_whole_vector() {
+        assert_eq!(IPoint2 { x: 0, y: 0 }.direction(), IPoint2 { x: 0, y: 0 });
+        assert_eq!(IPoint2 { x: 0, y: 5 }.direction(), IPoint2 { x: 0, y: 1 });
+        assert_eq!(IPoint2 { x: 0, y: -5 }.direction(), IPoint2 { x: 0, y: -1 });
+        assert_eq!(IPoint2 { x: 5, y: 0 }.direction(), IPoint2 { x: 1, y: 0 });
+        assert_eq!(IPoint2 { x: -5, y: 0 }.direction(), IPoint2 { x: -1, y: 0 });
+        assert_eq!(IPoint2 { x: 3, y: 3 }.direction(), IPoint2 { x: 1, y: 1 });
+        assert_eq!(IPoint2 { x: 4, y: 6 }.direction(), IPoint2 { x: 2, y: 3 });
         assert_eq!(
-            Point { x: 0, y: 0 }.smallest_whole_vector(),
-            Point { x: 0, y: 0 }
-        );
-        assert_eq!(
-            Point { x: 0, y: 5 }.smallest_whole_vector(),
-            Point { x: 0, y: 1 }
-        );
-        assert_eq!(
-            Point { x: 0, y: -5 }.smallest_whole_vector(),
-            Point { x: 0, y: -1 }
-        );
-        assert_eq!(
-            Point { x: 5, y: 0 }.smallest_whole_vector(),
-            Point { x: 1, y: 0 }
-        );
-        assert_eq!(
-            Point { x: -5, y: 0 }.smallest_whole_vector(),
-            Point { x: -1, y: 0 }
-        );
-        assert_eq!(
-            Point { x: 3, y: 3 }.smallest_whole_vector(),
-            Point { x: 1, y: 1 }
-        );
-        assert_eq!(
-            Point { x: 4, y: 6 }.smallest_whole_vector(),
-            Point { x: 2, y: 3 }
-        );
-        assert_eq!(
-            Point { x: 7, y: -10 }.smallest_whole_vector(),
-            Point { x: 7, y: -10 }
+            IPoint2 { x: 7, y: -10 }.direction(),
+            IPoint2 { x: 7, y: -10 }
         );
     }
 
@@ -413,7 +391,7 @@ mod tests {
 ...##
 ",
         )?;
-        assert_eq!(map.most_visible(), (vec![Point { x: 3, y: 4 }], 8));
+        assert_eq!(map.most_visible(), (vec![IPoint2 { x: 3, y: 4 }], 8));
 
         map = Map::parse(
             "
@@ -429,7 +407,7 @@ mod tests {
 .#....####
 ",
         )?;
-        assert_eq!(map.most_visible(), (vec![Point { x: 5, y: 8 }], 33));
+        assert_eq!(map.most_visible(), (vec![IPoint2 { x: 5, y: 8 }], 33));
 
         map = Map::parse(
             "
@@ -445,7 +423,7 @@ mod tests {
 .####.###.
 ",
         )?;
-        assert_eq!(map.most_visible(), (vec![Point { x: 1, y: 2 }], 35));
+        assert_eq!(map.most_visible(), (vec![IPoint2 { x: 1, y: 2 }], 35));
 
         map = Map::parse(
             "
@@ -461,7 +439,7 @@ mod tests {
 .....#.#..
 ",
         )?;
-        assert_eq!(map.most_visible(), (vec![Point { x: 6, y: 3 }], 41));
+        assert_eq!(map.most_visible(), (vec![IPoint2 { x: 6, y: 3 }], 41));
 
         map = Map::parse(
             "
@@ -487,7 +465,7 @@ mod tests {
 ###.##.####.##.#..##
 ",
         )?;
-        assert_eq!(map.most_visible(), (vec![Point { x: 11, y: 13 }], 210));
+        assert_eq!(map.most_visible(), (vec![IPoint2 { x: 11, y: 13 }], 210));
 
         Ok(())
     }
@@ -502,31 +480,132 @@ mod tests {
 ",
         )?;
         assert_eq!(
-            map.laser_targets(Point { x: 1, y: 1 }),
+            map.laser_targets(IPoint2 { x: 1, y: 1 }, Laser::default())
+                .collect::<Vec<IPoint2>>(),
             vec![
-                Point { x: 1, y: 0 },
-                Point { x: 2, y: 0 },
-                Point { x: 2, y: 1 },
-                Point { x: 2, y: 2 },
-                Point { x: 1, y: 2 },
-                Point { x: 0, y: 2 },
-                Point { x: 0, y: 1 },
-                Point { x: 0, y: 0 },
+                IPoint2 { x: 1, y: 0 },
+                IPoint2 { x: 2, y: 0 },
+                IPoint2 { x: 2, y: 1 },
+                IPoint2 { x: 2, y: 2 },
+                IPoint2 { x: 1, y: 2 },
+                IPoint2 { x: 0, y: 2 },
+                IPoint2 { x: 0, y: 1 },
+                IPoint2 { x: 0, y: 0 },
             ]
         );
         assert_eq!(
-            map.laser_targets(Point { x: 0, y: 1 }),
+            map.laser_targets(IPoint2 { x: 0, y: 1 }, Laser::default())
+                .collect::<Vec<IPoint2>>(),
             vec![
-                Point { x: 0, y: 0 },
-                Point { x: 1, y: 0 },
-                Point { x: 2, y: 0 },
-                Point { x: 1, y: 1 },
-                Point { x: 2, y: 2 },
-                Point { x: 1, y: 2 },
-                Point { x: 0, y: 2 },
-                Point { x: 2, y: 1 },
+                IPoint2 { x: 0, y: 0 },
+                IPoint2 { x: 1, y: 0 },
+                IPoint2 { x: 2, y: 0 },
+                IPoint2 { x: 1, y: 1 },
+                IPoint2 { x: 2, y: 2 },
+                IPoint2 { x: 1, y: 2 },
+                IPoint2 { x: 0, y: 2 },
+                IPoint2 { x: 2, y: 1 },
             ]
         );
         Ok(())
     }
+
+    #[test]
+    fn test_map_laser_targets_configured() -> Result<()> {
+        let map = Map::parse(
+            "
+###
+###
+###
+",
+        )?;
+        let from = IPoint2 { x: 1, y: 1 };
+        assert_eq!(
+            map.laser_targets(from, Laser::default().anticlockwise())
+                .take(3)
+                .collect::<Vec<IPoint2>>(),
+            vec![
+                IPoint2 { x: 1, y: 0 },
+                IPoint2 { x: 0, y: 0 },
+                IPoint2 { x: 0, y: 1 },
+            ]
+        );
+        assert_eq!(
+            map.laser_targets(from, Laser::default().start(IPoint2 { x: 3, y: 3 }))
+                .take(3)
+                .collect::<Vec<IPoint2>>(),
+            vec![
+                IPoint2 { x: 2, y: 2 },
+                IPoint2 { x: 1, y: 2 },
+                IPoint2 { x: 0, y: 2 },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_laser_cmp_angle() {
+        let laser = Laser::default();
+        let up = IPoint2 { x: 0, y: -1 };
+        // Too close together to tell apart reliably with floats
+        let a = IPoint2 {
+            x: 1,
+            y: -100_000_000,
+        };
+        let b = IPoint2 {
+            x: 1,
+            y: -99_999_999,
+        };
+        assert_eq!(laser.cmp_angle(up, a), Ordering::Less);
+        assert_eq!(laser.cmp_angle(a, b), Ordering::Less);
+        assert_eq!(laser.cmp_angle(b, a), Ordering::Greater);
+        assert_eq!(laser.cmp_angle(a, a), Ordering::Equal);
+        // Just short of a full turn comes last
+        assert_eq!(
+            laser.cmp_angle(IPoint2 { x: -1, y: -5 }, b),
+            Ordering::Greater
+        );
+        assert_eq!(
+            laser.anticlockwise().cmp_angle(IPoint2 { x: -1, y: -5 }, b),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn test_map_laser_targets_large() -> Result<()> {
+        let map = Map::parse(
+            "
+.#..##.###...#######
+##.############..##.
+.#.######.########.#
+.###.#######.####.#.
+#####.##.#.##.###.##
+..#####..#.#########
+####################
+#.####....###.#.#.##
+##.#################
+#####.##.###..####..
+..######..##.#######
+####.##.####...##..#
+.#####..#.######.###
+##...#.##########...
+#.##########.#######
+.####.#.###.###.#.##
+....##.##.###..#####
+.#.#.###########.###
+#.#.#.#####.####.###
+###.##.####.##.#..##
+",
+        )?;
+        let targets: Vec<IPoint2> = map
+            .laser_targets(IPoint2 { x: 11, y: 13 }, Laser::default())
+            .collect();
+        assert_eq!(targets[0], IPoint2 { x: 11, y: 12 });
+        assert_eq!(targets[1], IPoint2 { x: 12, y: 1 });
+        assert_eq!(targets[49], IPoint2 { x: 16, y: 9 });
+        assert_eq!(targets[199], IPoint2 { x: 8, y: 2 });
+        assert_eq!(targets[298], IPoint2 { x: 11, y: 1 });
+        assert_eq!(targets.len(), 299);
+        Ok(())
+    }
 }
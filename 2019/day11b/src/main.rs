@@ -1,29 +1,16 @@
 use anyhow::{anyhow, Context, Error, Result};
 
+use helper::dir::{Dir, Turtle, YAxis};
+use helper::grid::SparseGrid;
+use helper::image::{Image, Rgb};
+use helper::ocr;
+use helper::point::IPoint2;
 use intcptr;
 
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 use std::convert::TryFrom;
 use std::fmt;
 use std::io::{stdin, Read};
-use std::ops::Add;
-
-#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
-struct Point {
-    x: isize,
-    y: isize,
-}
-
-impl Add for Point {
-    type Output = Self;
-
-    fn add(self, other: Self) -> Self {
-        Self {
-            x: self.x + other.x,
-            y: self.y + other.y,
-        }
-    }
-}
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
 enum Colour {
@@ -71,106 +58,35 @@ impl fmt::Display for Colour {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-enum Direction {
-    Up,
-    Right,
-    Down,
-    Left,
-}
-
-impl Direction {
-    fn turn_left(&self) -> Direction {
-        match *self {
-            Direction::Up => Direction::Left,
-            Direction::Right => Direction::Up,
-            Direction::Down => Direction::Right,
-            Direction::Left => Direction::Down,
-        }
-    }
-
-    fn turn_right(&self) -> Direction {
-        match *self {
-            Direction::Up => Direction::Right,
-            Direction::Right => Direction::Down,
-            Direction::Down => Direction::Left,
-            Direction::Left => Direction::Up,
-        }
-    }
-
-    fn unit_vector(&self) -> Point {
-        match *self {
-            Direction::Up => Point { x: 0, y: -1 },
-            Direction::Right => Point { x: 1, y: 0 },
-            Direction::Down => Point { x: 0, y: 1 },
-            Direction::Left => Point { x: -1, y: 0 },
-        }
-    }
-}
-
-fn paint(mut prog: &mut intcptr::Program) -> Result<()> {
-    let mut ship: HashMap<Point, Colour> = HashMap::new();
-    ship.insert(Point { x: 0, y: 0 }, Colour::White);
-    let mut painted: HashSet<Point> = HashSet::new();
-    let mut loc = Point { x: 0, y: 0 };
-    let mut dir: Direction = Direction::Up;
+fn paint(prog: &mut intcptr::Program) -> Result<SparseGrid<Colour>> {
+    let mut ship: SparseGrid<Colour> = SparseGrid::new();
+    ship.insert(IPoint2 { x: 0, y: 0 }, Colour::White);
+    let mut painted: HashSet<IPoint2> = HashSet::new();
+    let mut robot = Turtle::new(IPoint2::default(), Dir::North, YAxis::Down);
 
     loop {
-        let cur_colour = ship.entry(loc).or_default();
-        let halt = intcptr::run(&mut prog, &[(*cur_colour).into()])?;
+        let cur_colour = ship.entry(robot.at).or_default();
+        let halt = intcptr::run(prog, &[(*cur_colour).into()])?;
         if halt.output.len() != 2 {
             return Err(anyhow!("Expected output len 2, got {}", halt.output.len()));
         }
         let colour = Colour::try_from(halt.output[0])?;
         if colour != *cur_colour {
             *cur_colour = colour;
-            painted.insert(loc);
+            painted.insert(robot.at);
         }
         match halt.output[1] {
-            0 => dir = dir.turn_left(),
-            1 => dir = dir.turn_right(),
+            0 => robot.turn_left(),
+            1 => robot.turn_right(),
             n => return Err(anyhow!("Expected direction 0 or 1, got {}", n)),
         }
-        loc = loc + dir.unit_vector();
+        robot.forward(1);
         if halt.cause == intcptr::HaltCause::Exit {
             break;
         }
     }
 
-    println!("{}", render(&ship));
-    Ok(())
-}
-
-fn render(ship: &HashMap<Point, Colour>) -> String {
-    let mut min_x: isize = 0;
-    let mut min_y: isize = 0;
-    let mut max_x: isize = 0;
-    let mut max_y: isize = 0;
-
-    for loc in ship.keys() {
-        if loc.x < min_x {
-            min_x = loc.x;
-        }
-        if loc.x > max_x {
-            max_x = loc.x;
-        }
-        if loc.y < min_y {
-            min_y = loc.y;
-        }
-        if loc.y > max_y {
-            max_y = loc.y;
-        }
-    }
-
-    (min_y..=max_y)
-        .map(|y| {
-            (min_x..=max_x)
-                .map(|x| format!("{}", ship.get(&Point { x, y }).cloned().unwrap_or_default()))
-                .collect::<Vec<String>>()
-                .join("")
-        })
-        .collect::<Vec<String>>()
-        .join("\n")
+    Ok(ship)
 }
 
 fn main() -> Result<()> {
@@ -182,6 +98,27 @@ fn main() -> Result<()> {
         .map(|w| w.parse::<isize>().context("failed parsing number"))
         .collect::<Result<Vec<isize>>>()?;
     let mut prog = intcptr::Program::with_memory(input);
-    paint(&mut prog)?;
+    let ship = paint(&mut prog)?;
+    println!("{}", ship);
+    println!(
+        "{}",
+        ocr::recognise_points(
+            ship.iter()
+                .filter(|(_, c)| **c == Colour::White)
+                .map(|(p, _)| p)
+        )?
+    );
+    // Optionally write the hull out, eg. `day11b --image hull.png < res/input`
+    if let Some(path) = helper::arg_value("image") {
+        Image::from_map(
+            &ship,
+            |c| match c {
+                Some(Colour::White) => Rgb::WHITE,
+                _ => Rgb::BLACK,
+            },
+            10,
+        )
+        .save(path)?;
+    }
     Ok(())
 }
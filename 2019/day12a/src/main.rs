@@ -1,8 +1,9 @@
-use anyhow::{anyhow, Result};
-use lazy_static::lazy_static;
-use regex::Regex;
+use anyhow::Result;
 
+use helper::image::{Image, Rgb};
 use helper::point::IPoint3;
+use helper::record::Recorder;
+use helper::scan;
 
 use std::cmp::Ordering;
 use std::io::{stdin, BufRead};
@@ -60,26 +61,25 @@ impl System {
     fn total_energy(&self) -> isize {
         self.0.iter().map(|b| b.total_energy()).sum()
     }
+
+    // Draws the bodies looking down the z axis, within `radius` of the origin
+    fn to_image(&self, radius: isize) -> Image {
+        let size = radius as usize * 2 + 1;
+        let mut image = Image::new(size, size, Rgb::BLACK);
+        let colours = [Rgb::RED, Rgb::GREEN, Rgb::BLUE, Rgb::WHITE];
+        for (body, colour) in self.0.iter().zip(colours.iter().cycle()) {
+            let (x, y) = (body.pos.x + radius - 1, body.pos.y + radius - 1);
+            if x >= 0 && y >= 0 {
+                image.fill_rect(x as usize, y as usize, 3, 3, *colour);
+            }
+        }
+        image
+    }
 }
 
 fn parse_ipoint3(input: &str) -> Result<IPoint3> {
-    lazy_static! {
-        static ref RE: Regex = Regex::new(r"<x=(-?\d+),y=(-?\d+),z=(-?\d+)>").unwrap();
-    }
-    let trimmed: String = input
-        .chars()
-        .filter(|c| !c.is_whitespace())
-        .collect::<String>();
-
-    let capture = RE
-        .captures_iter(&trimmed)
-        .next()
-        .ok_or_else(|| anyhow!("could not find point"))?;
-    Ok(IPoint3 {
-        x: capture[1].parse()?,
-        y: capture[2].parse()?,
-        z: capture[3].parse()?,
-    })
+    let (x, y, z) = scan!("<x={}, y={}, z={}>", isize, isize, isize)(input)?;
+    Ok(IPoint3 { x, y, z })
 }
 
 fn gravity_change_1d(a: isize, b: isize) -> isize {
@@ -113,9 +113,17 @@ fn main() -> Result<()> {
             .map(|l| Ok(Body::with_pos(parse_ipoint3(&l?)?)))
             .collect::<Result<Vec<Body>>>()?,
     );
+    // Optionally record the motion, eg. `day12a --record moons.gif < res/input`
+    let mut recorder = helper::arg_value("record").map(|path| Recorder::new(path).every(2));
     for _ in 0..1000 {
+        if let Some(r) = recorder.as_mut() {
+            r.step(|| system.to_image(100))?;
+        }
         system.step();
     }
+    if let Some(r) = recorder {
+        r.finish()?;
+    }
     println!("{}", system.total_energy());
     Ok(())
 }
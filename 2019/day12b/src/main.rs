@@ -1,31 +1,27 @@
 use anyhow::{anyhow, Result};
-use lazy_static::lazy_static;
-use num::integer::lcm;
-use regex::Regex;
 
-use helper::point::ipoint3::Field;
-use helper::point::IPoint3;
+use helper::cycle::{brent, Cycle};
+use helper::point::{IPoint, IPoint3};
+use helper::scan;
 
 use std::cmp::Ordering;
-use std::collections::HashMap;
-use std::hash::Hash;
 use std::io::{stdin, BufRead};
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
-struct Body {
-    pos: IPoint3,
-    vel: IPoint3,
+struct Body<const N: usize> {
+    pos: IPoint<N>,
+    vel: IPoint<N>,
 }
 
-impl Body {
-    fn with_pos(pos: IPoint3) -> Self {
+impl<const N: usize> Body<N> {
+    fn with_pos(pos: IPoint<N>) -> Self {
         Self {
             pos,
             vel: Default::default(),
         }
     }
 
-    fn axis(&self, axis: Field) -> Body1 {
+    fn axis(&self, axis: usize) -> Body1 {
         Body1 {
             pos: self.pos[axis],
             vel: self.vel[axis],
@@ -33,24 +29,29 @@ impl Body {
     }
 }
 
+/// Any number of bodies moving in any number of dimensions.
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
-struct System(Vec<Body>);
-
-impl System {
-    fn loop_steps(&self) -> usize {
-        let (x_start, x_len) = self.axis(Field::X).loop_steps();
-        let (y_start, y_len) = self.axis(Field::Y).loop_steps();
-        let (z_start, z_len) = self.axis(Field::Z).loop_steps();
-
-        if x_start > 0 || y_start > 0 || z_start > 0 {
-            unimplemented!("loop_steps not implemented for loop start > 0");
-        }
+struct System<const N: usize>(Vec<Body<N>>);
+
+impl<const N: usize> System<N> {
+    /// The number of steps until the system is first in a state it's been in
+    /// before.
+    fn loop_steps(&self) -> Result<usize> {
+        let cycle = self.cycle()?;
+        cycle
+            .start
+            .checked_add(cycle.len)
+            .ok_or_else(|| anyhow!("loop of {} steps is too long", cycle.len))
+    }
 
-        // Have only implemented for all starts = 0, as that produces the correct answer
-        lcm(lcm(x_len, y_len), lcm(y_len, z_len))
+    // Each axis moves independently of the others, so the cycle of the whole
+    // system comes from the cycles of each axis, which are much shorter
+    fn cycle(&self) -> Result<Cycle> {
+        Cycle::combine((0..N).map(|axis| self.axis(axis).cycle()))
+            .ok_or_else(|| anyhow!("cycle of the whole system is too long"))
     }
 
-    fn axis(&self, axis: Field) -> System1 {
+    fn axis(&self, axis: usize) -> System1 {
         System1(self.0.iter().map(|b| b.axis(axis)).collect())
     }
 }
@@ -71,7 +72,7 @@ impl System1 {
     }
 
     fn apply_gravity(&mut self) {
-        for a_index in 0..self.0.len() - 1 {
+        for a_index in 0..self.0.len().saturating_sub(1) {
             for b_index in a_index + 1..self.0.len() {
                 let (new_a, new_b) = gravity1(self.0[a_index], self.0[b_index]);
                 self.0[a_index] = new_a;
@@ -84,40 +85,18 @@ impl System1 {
         self.0.iter_mut().for_each(|body| body.pos += body.vel);
     }
 
-    fn loop_steps(&self) -> (usize, usize) {
-        let mut iter = self.clone();
-        let mut previous: HashMap<System1, usize> = HashMap::new();
-        let mut i = 0;
-        previous.insert(iter.clone(), i);
-        loop {
-            i += 1;
-            iter.step();
-            if let Some(pos) = previous.get(&iter) {
-                return (*pos, i - pos);
-            }
-            previous.insert(iter.clone(), i);
-        }
+    fn cycle(&self) -> Cycle {
+        brent(self.clone(), |s| {
+            let mut next = s.clone();
+            next.step();
+            next
+        })
     }
 }
 
 fn parse_ipoint3(input: &str) -> Result<IPoint3> {
-    lazy_static! {
-        static ref RE: Regex = Regex::new(r"<x=(-?\d+),y=(-?\d+),z=(-?\d+)>").unwrap();
-    }
-    let trimmed: String = input
-        .chars()
-        .filter(|c| !c.is_whitespace())
-        .collect::<String>();
-
-    let capture = RE
-        .captures_iter(&trimmed)
-        .next()
-        .ok_or_else(|| anyhow!("could not find point"))?;
-    Ok(IPoint3 {
-        x: capture[1].parse()?,
-        y: capture[2].parse()?,
-        z: capture[3].parse()?,
-    })
+    let (x, y, z) = scan!("<x={}, y={}, z={}>", isize, isize, isize)(input)?;
+    Ok(IPoint3 { x, y, z })
 }
 
 fn gravity_change1(a: isize, b: isize) -> isize {
@@ -136,14 +115,14 @@ fn gravity1(mut a: Body1, mut b: Body1) -> (Body1, Body1) {
 }
 
 fn main() -> Result<()> {
-    let system: System = System(
+    let system: System<3> = System(
         stdin()
             .lock()
             .lines()
-            .map(|l| Ok(Body::with_pos(parse_ipoint3(&l?)?)))
-            .collect::<Result<Vec<Body>>>()?,
+            .map(|l| Ok(Body::with_pos(parse_ipoint3(&l?)?.into())))
+            .collect::<Result<Vec<Body<3>>>>()?,
     );
-    println!("{}", system.loop_steps());
+    println!("{}", system.loop_steps()?);
     Ok(())
 }
 
@@ -181,18 +160,55 @@ mod tests {
     }
 
     #[test]
-    fn test_system_loop_steps() {
-        let system = System(vec![
-            Body::with_pos(IPoint3 { x: -1, y: 0, z: 2 }),
-            Body::with_pos(IPoint3 {
-                x: 2,
-                y: -10,
-                z: -7,
-            }),
-            Body::with_pos(IPoint3 { x: 4, y: -8, z: 8 }),
-            Body::with_pos(IPoint3 { x: 3, y: 5, z: -1 }),
-        ]);
+    fn test_system_loop_steps() -> Result<()> {
+        let system = System(
+            [[-1, 0, 2], [2, -10, -7], [4, -8, 8], [3, 5, -1]]
+                .iter()
+                .map(|&p| Body::with_pos(p.into()))
+                .collect(),
+        );
+        assert_eq!(system.loop_steps()?, 2772);
 
-        assert_eq!(system.loop_steps(), 2772);
+        let system = System(
+            [[-8, -10, 0], [5, 5, 10], [2, -7, 3], [9, -8, -3]]
+                .iter()
+                .map(|&p| Body::with_pos(p.into()))
+                .collect(),
+        );
+        assert_eq!(system.loop_steps()?, 4_686_774_924);
+        Ok(())
+    }
+
+    #[test]
+    fn test_system_loop_steps_any_size() -> Result<()> {
+        // Finding the cycle of the whole system directly agrees with
+        // combining the axes, for other numbers of bodies and dimensions
+        let step = |s: &System1| {
+            let mut next = s.clone();
+            next.step();
+            next
+        };
+        let system: System<2> = System(
+            [[1, -2], [-3, 4], [2, 2], [0, -1]]
+                .iter()
+                .map(|&p| Body::with_pos(p.into()))
+                .collect(),
+        );
+        let whole = brent((system.axis(0), system.axis(1)), |(x, y)| {
+            (step(x), step(y))
+        });
+        assert_eq!(system.cycle()?, whole);
+        assert_eq!(system.loop_steps()?, 36);
+
+        let system: System<1> = System(vec![Body::with_pos([3].into())]);
+        assert_eq!(system.loop_steps()?, 1);
+        let system: System<4> = System(
+            [[1, 0, -1, 2], [0, 2, 1, -1], [-1, 1, 0, 0]]
+                .iter()
+                .map(|&p| Body::with_pos(p.into()))
+                .collect(),
+        );
+        assert_eq!(system.loop_steps()?, 20);
+        Ok(())
     }
 }
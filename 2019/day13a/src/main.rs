@@ -1,7 +1,10 @@
 use anyhow::{anyhow, Context, Result};
 
+use std::collections::HashMap;
 use std::io::{stdin, Read};
 
+use helper::point::IPoint2;
+use helper::render::{Colour, Glyph, Renderer};
 use intcptr::{run, Program};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -24,6 +27,16 @@ impl Tile {
             _ => Err(anyhow!("invalid tile {}", input)),
         }
     }
+
+    fn glyph(self) -> Glyph {
+        match self {
+            Tile::Empty => ' '.into(),
+            Tile::Wall => Glyph::new('#', Colour::White),
+            Tile::Block => Glyph::new('=', Colour::Blue),
+            Tile::Paddle => Glyph::new('_', Colour::Yellow),
+            Tile::Ball => Glyph::new('o', Colour::Red),
+        }
+    }
 }
 
 fn main() -> Result<()> {
@@ -37,17 +50,27 @@ fn main() -> Result<()> {
     let mut prog = Program::with_memory(input);
     let output = run(&mut prog, &[2])?.output;
 
-    let mut block_count = 0;
-    for (index, _x) in output.iter().enumerate().step_by(3) {
+    let mut screen: HashMap<(isize, isize), Tile> = HashMap::new();
+    for (index, x) in output.iter().enumerate().step_by(3) {
         if output.len() <= index + 2 {
             break;
         }
-        let _y = output[index + 1];
-        let tile = Tile::parse(output[index + 2])?;
-        if tile == Tile::Block {
-            block_count += 1;
-        }
+        let y = output[index + 1];
+        screen.insert((*x, y), Tile::parse(output[index + 2])?);
+    }
+    // Without a quarter there's only the one frame to watch, `day13b --play`
+    // is the playable version
+    if helper::has_arg("watch") {
+        let points: HashMap<IPoint2, Tile> = screen
+            .iter()
+            .map(|(&(x, y), t)| (IPoint2 { x, y }, *t))
+            .collect();
+        println!(
+            "{}",
+            Renderer::new(|t: &Tile| t.glyph()).ansi().render(&points)
+        );
     }
+    let block_count = screen.values().filter(|t| **t == Tile::Block).count();
     println!("{}", block_count);
 
     Ok(())
@@ -1,10 +1,41 @@
 use anyhow::{anyhow, Context, Result};
 
+use crossterm::event::{self, Event, KeyCode};
+use crossterm::terminal;
+
 use std::cmp::Ordering;
 use std::collections::HashMap;
-use std::io::{stdin, Read};
+use std::io::{stdin, stdout, Read, Write};
+use std::thread;
+use std::time::Duration;
 
-use intcptr::{run, Program};
+use helper::image::{Image, Rgb};
+use helper::point::IPoint2;
+use helper::record::Recorder;
+use helper::render::{Colour, Glyph, Renderer};
+use intcptr::{run, HaltCause, Program};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Mode {
+    // Plays itself without drawing anything
+    Autopilot,
+    // Plays itself, drawing each frame
+    Watch,
+    // Draws each frame and takes the joystick from the arrow keys
+    Play,
+}
+
+impl Mode {
+    fn from_args() -> Self {
+        if helper::has_arg("play") {
+            Mode::Play
+        } else if helper::has_arg("watch") {
+            Mode::Watch
+        } else {
+            Mode::Autopilot
+        }
+    }
+}
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 enum Tile {
@@ -26,6 +57,104 @@ impl Tile {
             _ => Err(anyhow!("invalid tile {}", input)),
         }
     }
+
+    fn colour(self) -> Rgb {
+        match self {
+            Tile::Empty => Rgb::BLACK,
+            Tile::Wall => Rgb::GREY,
+            Tile::Block => Rgb::BLUE,
+            Tile::Paddle => Rgb::WHITE,
+            Tile::Ball => Rgb::RED,
+        }
+    }
+
+    fn glyph(self) -> Glyph {
+        match self {
+            Tile::Empty => ' '.into(),
+            Tile::Wall => Glyph::new('#', Colour::White),
+            Tile::Block => Glyph::new('=', Colour::Blue),
+            Tile::Paddle => Glyph::new('_', Colour::Yellow),
+            Tile::Ball => Glyph::new('o', Colour::Red),
+        }
+    }
+}
+
+fn to_points(screen: &HashMap<(isize, isize), Tile>) -> HashMap<IPoint2, Tile> {
+    screen
+        .iter()
+        .map(|(&(x, y), t)| (IPoint2 { x, y }, *t))
+        .collect()
+}
+
+fn to_image(screen: &HashMap<(isize, isize), Tile>) -> Image {
+    Image::from_map(
+        &to_points(screen),
+        |t| t.map(|t| t.colour()).unwrap_or_default(),
+        4,
+    )
+}
+
+// Redraws the screen over the top of the last frame
+fn draw(screen: &HashMap<(isize, isize), Tile>, score: isize) -> Result<()> {
+    let rendered = Renderer::new(|t: &Tile| t.glyph())
+        .ansi()
+        .render(&to_points(screen));
+    let mut out = stdout();
+    // Raw mode doesn't return to the start of the line on a newline
+    write!(
+        out,
+        "\x1b[H{}\r\nScore: {}\x1b[K\r\n",
+        rendered.replace('\n', "\r\n"),
+        score
+    )?;
+    out.flush()?;
+    Ok(())
+}
+
+// Clears the terminal and hides the cursor to draw on, optionally putting it
+// in raw mode so keys can be read as they are pressed. The terminal is
+// restored when dropped, even if the game stops with an error.
+struct Terminal {
+    raw: bool,
+}
+
+impl Terminal {
+    fn enable(raw: bool) -> Result<Self> {
+        if raw {
+            terminal::enable_raw_mode()?;
+        }
+        let term = Terminal { raw };
+        print!("\x1b[2J\x1b[?25l");
+        stdout().flush()?;
+        Ok(term)
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        print!("\x1b[?25h");
+        let _ = stdout().flush();
+        if self.raw {
+            let _ = terminal::disable_raw_mode();
+        }
+    }
+}
+
+// Reads the joystick from the arrow keys, leaving it neutral if nothing is
+// pressed in time. Returns `None` if the player quits.
+fn read_joystick() -> Result<Option<isize>> {
+    if !event::poll(Duration::from_millis(150))? {
+        return Ok(Some(0));
+    }
+    Ok(match event::read()? {
+        Event::Key(key) => match key.code {
+            KeyCode::Left => Some(-1),
+            KeyCode::Right => Some(1),
+            KeyCode::Esc | KeyCode::Char('q') => None,
+            _ => Some(0),
+        },
+        _ => Some(0),
+    })
 }
 
 fn main() -> Result<()> {
@@ -40,16 +169,26 @@ fn main() -> Result<()> {
     // Free play, no quarters required
     prog.set(0, 2);
 
+    let mode = Mode::from_args();
+    let term = match mode {
+        Mode::Autopilot => None,
+        _ => Some(Terminal::enable(mode == Mode::Play)?),
+    };
+
     let mut joystick_next: Option<isize> = None;
     let mut screen: HashMap<(isize, isize), Tile> = HashMap::new();
     let mut score = 0;
+    // Optionally record the game, eg. `day13b --record game.gif < res/input`
+    let mut recorder =
+        helper::arg_value("record").map(|path| Recorder::new(path).every(4).max_frames(1000));
 
-    loop {
+    let outcome = loop {
         let input = match joystick_next {
             Some(j) => vec![j],
             None => vec![],
         };
-        let output = run(&mut prog, &input)?.output;
+        let halt = run(&mut prog, &input)?;
+        let output = halt.output;
 
         for (index, x) in output.iter().enumerate().step_by(3) {
             if output.len() <= index + 2 {
@@ -65,6 +204,13 @@ fn main() -> Result<()> {
             }
         }
 
+        if let Some(r) = recorder.as_mut() {
+            r.step(|| to_image(&screen))?;
+        }
+        if mode != Mode::Autopilot {
+            draw(&screen, score)?;
+        }
+
         let mut block_count = 0;
         let mut ball_x = 0;
         let mut paddle_x = 0;
@@ -79,18 +225,37 @@ fn main() -> Result<()> {
         }
 
         if block_count == 0 {
-            println!("Game over");
             // Game over!
-            break;
+            break "Game over".to_string();
         }
-
-        match paddle_x.cmp(&ball_x) {
-            Ordering::Greater => joystick_next = Some(-1),
-            Ordering::Less => joystick_next = Some(1),
-            Ordering::Equal => joystick_next = Some(0),
+        if halt.cause == HaltCause::Exit {
+            // The ball was missed
+            break format!("Game over, {} blocks left", block_count);
         }
-    }
+
+        joystick_next = match mode {
+            Mode::Play => match read_joystick()? {
+                Some(j) => Some(j),
+                None => break "Quit".to_string(),
+            },
+            _ => {
+                if mode == Mode::Watch {
+                    thread::sleep(Duration::from_millis(10));
+                }
+                Some(match paddle_x.cmp(&ball_x) {
+                    Ordering::Greater => -1,
+                    Ordering::Less => 1,
+                    Ordering::Equal => 0,
+                })
+            }
+        };
+    };
+    drop(term);
+    println!("{}", outcome);
     println!("{}", score);
+    if let Some(r) = recorder {
+        r.finish()?;
+    }
 
     Ok(())
 }
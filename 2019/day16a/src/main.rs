@@ -1,44 +1,41 @@
-use anyhow::{anyhow, Result};
+use anyhow::Result;
+use shared::fft::{digits, fft};
 
 use std::io::{stdin, Read};
 use std::iter;
 
-const BASE_PATTERN: &[isize] = &[0, 1, 0, -1];
+const BASE_PATTERN: &[i32] = &[0, 1, 0, -1];
 
-struct Pattern(Vec<isize>);
+struct Pattern(Vec<i32>);
 
 impl Pattern {
-    fn nth(&self, n: usize) -> Vec<isize> {
+    fn nth(&self, n: usize) -> Vec<i32> {
         if n == 0 {
             panic!("n must be non-zero");
         }
-        self.0
-            .iter()
-            .map(|v| iter::repeat(*v).take(n).collect::<Vec<isize>>())
-            .flatten()
-            .collect()
+        self.0.iter().flat_map(|v| iter::repeat_n(*v, n)).collect()
     }
 
-    fn apply(&self, input: Vec<isize>) -> Vec<isize> {
-        let mut output = vec![0isize; input.len()];
+    fn apply(&self, input: Vec<i32>) -> Vec<i32> {
+        let mut output = vec![0i32; input.len()];
         for (i, output_item) in output.iter_mut().enumerate().take(input.len()) {
             let p = self.nth(i + 1);
             *output_item = input
                 .iter()
                 .enumerate()
                 .map(|(k, v)| v * p[(k + 1) % p.len()])
-                .sum::<isize>()
+                .sum::<i32>()
                 .to_string()
                 .chars()
                 .last()
                 .unwrap()
                 .to_digit(10)
-                .unwrap() as isize;
+                .unwrap() as i32;
         }
         output
     }
 
-    fn apply_n(&self, mut input: Vec<isize>, n: usize) -> Vec<isize> {
+    fn apply_n(&self, mut input: Vec<i32>, n: usize) -> Vec<i32> {
         for _ in 0..n {
             input = self.apply(input);
         }
@@ -49,21 +46,19 @@ impl Pattern {
 fn main() -> Result<()> {
     let mut input = String::new();
     stdin().lock().read_to_string(&mut input)?;
-    let ints: Vec<isize> = input
-        .trim()
-        .chars()
-        .map(|c| {
-            c.to_digit(10)
-                .map(|d| d as isize)
-                .ok_or_else(|| anyhow!("not a digit"))
-        })
-        .collect::<Result<Vec<isize>>>()?;
-    let pattern = Pattern(BASE_PATTERN.to_vec());
+    let ints = digits(input.trim())?;
+    // The naive pattern is much slower but is kept to check against
+    let output = if helper::has_arg("naive") {
+        Pattern(BASE_PATTERN.to_vec()).apply_n(ints, 100)
+    } else {
+        fft(&ints, 0, 100)
+    };
+    // The answer is the first eight digits of the output
     println!(
         "{}",
-        pattern
-            .apply_n(ints, 100)
+        output
             .into_iter()
+            .take(8)
             .map(|d| d.to_string())
             .collect::<Vec<String>>()
             .join("")
@@ -84,7 +79,7 @@ mod tests {
 
     #[test]
     fn test_pattern_apply() {
-        let input: Vec<isize> = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        let input: Vec<i32> = vec![1, 2, 3, 4, 5, 6, 7, 8];
         let pattern = Pattern(BASE_PATTERN.to_vec());
         assert_eq!(
             pattern.apply_n(input.clone(), 1),
@@ -103,4 +98,44 @@ mod tests {
             vec![0, 1, 0, 2, 9, 4, 9, 8]
         );
     }
+
+    // Generates a pseudorandom signal so the FFT can be checked on more than
+    // the examples
+    fn signal(len: usize, seed: usize) -> Vec<i32> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1);
+                ((state >> 33) % 10) as i32
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fft_matches_pattern() -> Result<()> {
+        let pattern = Pattern(BASE_PATTERN.to_vec());
+        let mut signals = vec![
+            digits("12345678")?,
+            digits("80871224585914546619083218645595")?,
+            digits("19617804207202209144916044189917")?,
+        ];
+        signals.extend((1..50).map(|len| signal(len, len)));
+        for ints in signals {
+            let expected = pattern.apply_n(ints.clone(), 5);
+            // Any offset, including the first half where the pattern's
+            // negative runs matter
+            for start in 0..ints.len() {
+                assert_eq!(
+                    fft(&ints[start..], start, 5),
+                    &expected[start..],
+                    "signal {:?} start {}",
+                    ints,
+                    start
+                );
+            }
+        }
+        Ok(())
+    }
 }
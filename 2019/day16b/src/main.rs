@@ -1,39 +1,38 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use shared::fft::{digits, fft};
 
 use std::io::{stdin, Read};
 
-fn sum_of_following(input: &mut Vec<i8>) {
-    let mut tally: isize = 0;
-    input.iter_mut().rev().for_each(|v| {
-        tally += *v as isize;
-        *v = (tally % 10) as i8;
-    })
+/// Finds the message in the real signal, which is the input repeated, at the
+/// offset given by its first seven digits.
+fn decode(input: &str, repeat: usize) -> Result<String> {
+    let digits = digits(input)?;
+    let offset: usize = input
+        .get(0..7)
+        .ok_or_else(|| anyhow!("input too short"))?
+        .parse()?;
+    let len = repeat * digits.len();
+    if offset + 8 > len {
+        return Err(anyhow!("offset {} is past the end of the signal", offset));
+    }
+    // Only the signal from the offset onwards affects the message
+    let signal: Vec<i32> = digits
+        .iter()
+        .cycle()
+        .skip(offset % digits.len())
+        .take(len - offset)
+        .copied()
+        .collect();
+    Ok(fft(&signal, offset, 100)[..8]
+        .iter()
+        .map(|v| v.to_string())
+        .collect())
 }
 
 fn main() -> Result<()> {
     let mut input = String::new();
     stdin().lock().read_to_string(&mut input)?;
-    let trimmed = input.trim();
-    let offset: usize = trimmed[0..7].parse()?;
-    let mut ints: Vec<i8> = trimmed
-        .bytes()
-        .map(|b| (b - b'0') as i8)
-        .cycle()
-        .take(10_000 * trimmed.len())
-        .collect();
-
-    // We now work on the assumption that the offset it in the second half of output, and the second
-    // half of the pattern is just the sum of itself plus the following.
-    for _ in 0..100 {
-        sum_of_following(&mut ints);
-    }
-    println!(
-        "{}",
-        &ints[offset..offset + 8]
-            .iter()
-            .map(|v| format!("{}", v))
-            .collect::<String>()
-    );
+    println!("{}", decode(input.trim(), 10_000)?);
     Ok(())
 }
 
@@ -41,10 +40,49 @@ fn main() -> Result<()> {
 mod tests {
     use super::*;
 
+    // In the second half of the signal each digit is just the sum of itself
+    // and those following it
+    fn sum_of_following(input: &mut [i32]) {
+        let mut tally = 0;
+        input.iter_mut().rev().for_each(|v| {
+            tally += *v;
+            *v = tally % 10;
+        })
+    }
+
     #[test]
     fn test_sum_of_following() {
-        let mut ints: Vec<i8> = vec![0, 1, 5, 9];
+        let mut ints = vec![0, 1, 5, 9];
         sum_of_following(&mut ints);
         assert_eq!(ints, vec![5, 5, 4, 9]);
     }
+
+    #[test]
+    fn test_fft_second_half() {
+        let input: Vec<i32> = (0..40).map(|i| (i * 7 + 3) % 10).collect();
+        let mut expected = input[20..].to_vec();
+        for _ in 0..10 {
+            sum_of_following(&mut expected);
+        }
+        assert_eq!(fft(&input[20..], 20, 10), expected);
+    }
+
+    #[test]
+    fn test_decode() -> Result<()> {
+        assert_eq!(
+            decode("03036732577212944063491565474664", 10_000)?,
+            "84462026"
+        );
+        assert_eq!(
+            decode("02935109699940807407585447034323", 10_000)?,
+            "78725270"
+        );
+        assert_eq!(
+            decode("03081770884921959731165446850517", 10_000)?,
+            "53553731"
+        );
+        // An offset in the first half of the signal
+        assert_eq!(decode("00000031234567890123", 2)?, "21065584");
+        Ok(())
+    }
 }
@@ -1,9 +1,11 @@
 use anyhow::{anyhow, Context, Result};
 
+use helper::dir::{Dir, YAxis};
+use helper::grid::SparseGrid;
 use helper::point::IPoint2;
+use helper::render::{DisplayPalette, Renderer};
 use intcptr::{run, Program};
 
-use std::collections::HashMap;
 use std::fmt;
 use std::io::{stdin, Read};
 
@@ -36,59 +38,9 @@ impl fmt::Display for Tile {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
-enum Dir {
-    North,
-    South,
-    West,
-    East,
-}
-
-impl Dir {
-    fn parse(input: char) -> Result<Self> {
-        match input {
-            '^' => Ok(Dir::North),
-            'v' => Ok(Dir::South),
-            '<' => Ok(Dir::West),
-            '>' => Ok(Dir::East),
-            _ => Err(anyhow!("invalid dir: {}", input)),
-        }
-    }
-}
-
-impl Dir {
-    fn unit(self) -> IPoint2 {
-        match self {
-            Dir::North => IPoint2 { x: 0, y: -1 },
-            Dir::South => IPoint2 { x: 0, y: 1 },
-            Dir::West => IPoint2 { x: -1, y: 0 },
-            Dir::East => IPoint2 { x: 1, y: 0 },
-        }
-    }
-
-    fn variants() -> Vec<Dir> {
-        vec![Dir::North, Dir::South, Dir::West, Dir::East]
-    }
-}
-
-impl fmt::Display for Dir {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                Dir::North => '^',
-                Dir::South => 'v',
-                Dir::West => '<',
-                Dir::East => '>',
-            }
-        )
-    }
-}
-
 #[derive(Debug, PartialEq, Eq, Clone)]
 struct World {
-    tiles: HashMap<IPoint2, Tile>,
+    tiles: SparseGrid<Tile>,
     at: IPoint2,
     dir: Dir,
 }
@@ -96,7 +48,7 @@ struct World {
 impl Default for World {
     fn default() -> Self {
         World {
-            tiles: HashMap::new(),
+            tiles: SparseGrid::new(),
             at: IPoint2::default(),
             dir: Dir::North,
         }
@@ -126,23 +78,6 @@ impl World {
         Ok(world)
     }
 
-    fn bounds(&self) -> (IPoint2, IPoint2) {
-        if self.tiles.is_empty() {
-            return (IPoint2::default(), IPoint2::default());
-        }
-        let mut locs = self.tiles.keys();
-        let first = locs.next().unwrap();
-        let mut min = *first;
-        let mut max = *first;
-        for l in locs {
-            min.x = isize::min(min.x, l.x);
-            min.y = isize::min(min.y, l.y);
-            max.x = isize::max(max.x, l.x);
-            max.y = isize::max(max.y, l.y);
-        }
-        (min, max)
-    }
-
     fn intersections(&self) -> Vec<IPoint2> {
         self.tiles
             .iter()
@@ -150,11 +85,13 @@ impl World {
                 if tile == &Tile::Scaffold
                     && Dir::variants()
                         .into_iter()
-                        .filter(|dir| self.tiles.get(&(*loc + dir.unit())) == Some(&Tile::Scaffold))
+                        .filter(|dir| {
+                            self.tiles.get(loc + dir.unit(YAxis::Down)) == Some(&Tile::Scaffold)
+                        })
                         .count()
                         > 2
                 {
-                    Some(*loc)
+                    Some(loc)
                 } else {
                     None
                 }
@@ -165,27 +102,13 @@ impl World {
 
 impl fmt::Display for World {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let (min, max) = self.bounds();
         write!(
             f,
             "{}",
-            (min.y..=max.y)
-                .map(|y| (min.x..=max.x)
-                    .map(|x| {
-                        let loc = IPoint2 { x, y };
-                        if loc == self.at {
-                            self.dir.to_string()
-                        } else {
-                            self.tiles
-                                .get(&loc)
-                                .cloned()
-                                .unwrap_or(Tile::Space)
-                                .to_string()
-                        }
-                    })
-                    .collect::<String>())
-                .collect::<Vec<String>>()
-                .join("\n")
+            Renderer::new(DisplayPalette)
+                .blank('.')
+                .highlight(vec![self.at], self.dir.arrow())
+                .render(&self.tiles)
         )
     }
 }
@@ -0,0 +1,389 @@
+use anyhow::{anyhow, Result};
+use rayon::prelude::*;
+
+use std::collections::VecDeque;
+
+use crate::{run, HaltCause, Program};
+
+/// How the amplifiers are wired together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Topology {
+    /// Each amplifier feeds the next, with the last giving the signal.
+    Chain,
+    /// A chain where the last amplifier also feeds back into the first,
+    /// running until the programs stop.
+    Feedback,
+    /// Arbitrary `(from, to)` wiring without cycles. Amplifiers without any
+    /// inputs get the initial signal, and the one amplifier without outputs
+    /// gives the signal.
+    Dag(Vec<(usize, usize)>),
+}
+
+/// The outputs of each amplifier, along with which get the initial signal
+/// and which gives the final signal.
+struct Wiring {
+    outputs: Vec<Vec<usize>>,
+    sources: Vec<usize>,
+    sink: usize,
+}
+
+impl Topology {
+    fn wiring(&self, amps: usize) -> Result<Wiring> {
+        if amps == 0 {
+            return Err(anyhow!("need at least one amplifier"));
+        }
+        let chain = (1..amps).map(|to| (to - 1, to));
+        match self {
+            Topology::Chain => Ok(Wiring {
+                outputs: wire(amps, chain)?,
+                sources: vec![0],
+                sink: amps - 1,
+            }),
+            Topology::Feedback => Ok(Wiring {
+                outputs: wire(amps, chain.chain(vec![(amps - 1, 0)]))?,
+                sources: vec![0],
+                sink: amps - 1,
+            }),
+            Topology::Dag(edges) => {
+                let outputs = wire(amps, edges.iter().cloned())?;
+                let mut incoming = vec![0; amps];
+                for &to in outputs.iter().flatten() {
+                    incoming[to] += 1;
+                }
+                let sources: Vec<usize> = (0..amps).filter(|&a| incoming[a] == 0).collect();
+
+                // Kahn's algorithm only reaches every amplifier without cycles
+                let mut queue: VecDeque<usize> = sources.iter().cloned().collect();
+                let mut reached = 0;
+                while let Some(from) = queue.pop_front() {
+                    reached += 1;
+                    for &to in &outputs[from] {
+                        incoming[to] -= 1;
+                        if incoming[to] == 0 {
+                            queue.push_back(to);
+                        }
+                    }
+                }
+                if reached != amps {
+                    return Err(anyhow!("amplifier wiring has a cycle"));
+                }
+
+                let sinks: Vec<usize> = (0..amps).filter(|&a| outputs[a].is_empty()).collect();
+                match sinks.as_slice() {
+                    [sink] => Ok(Wiring {
+                        outputs,
+                        sources,
+                        sink: *sink,
+                    }),
+                    _ => Err(anyhow!(
+                        "expected one amplifier without outputs, found {}",
+                        sinks.len()
+                    )),
+                }
+            }
+        }
+    }
+}
+
+fn wire<I>(amps: usize, edges: I) -> Result<Vec<Vec<usize>>>
+where
+    I: IntoIterator<Item = (usize, usize)>,
+{
+    let mut outputs = vec![vec![]; amps];
+    for (from, to) in edges {
+        if from >= amps || to >= amps {
+            return Err(anyhow!(
+                "edge {} -> {} out of range for {} amplifiers",
+                from,
+                to,
+                amps
+            ));
+        }
+        outputs[from].push(to);
+    }
+    Ok(outputs)
+}
+
+/// Whether the search is after the highest or lowest signal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Objective {
+    Maximise,
+    Minimise,
+}
+
+impl Objective {
+    fn better(&self, signal: isize, than: isize) -> bool {
+        match self {
+            Objective::Maximise => signal > than,
+            Objective::Minimise => signal < than,
+        }
+    }
+}
+
+/// The phases which gave the best signal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Best {
+    pub phases: Vec<isize>,
+    pub signal: isize,
+}
+
+/// A search for the phase settings giving the best signal from a set of
+/// amplifiers, each given a different phase from the allowed set.
+pub struct Search {
+    programs: Vec<Program>,
+    phases: Vec<isize>,
+    topology: Topology,
+    objective: Objective,
+    input: isize,
+}
+
+impl Search {
+    /// Amplifiers all running the same program, defaulting to a chain with
+    /// phases `0..amps` looking for the highest signal from an input of 0.
+    pub fn new(program: Program, amps: usize) -> Self {
+        Self::with_programs(vec![program; amps])
+    }
+
+    /// Amplifiers each running their own program.
+    pub fn with_programs(programs: Vec<Program>) -> Self {
+        Self {
+            phases: (0..programs.len() as isize).collect(),
+            programs,
+            topology: Topology::Chain,
+            objective: Objective::Maximise,
+            input: 0,
+        }
+    }
+
+    pub fn phases(mut self, phases: &[isize]) -> Self {
+        self.phases = phases.to_vec();
+        self
+    }
+
+    pub fn topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    pub fn objective(mut self, objective: Objective) -> Self {
+        self.objective = objective;
+        self
+    }
+
+    pub fn input(mut self, input: isize) -> Self {
+        self.input = input;
+        self
+    }
+
+    /// Every ordering of distinct phases from the set, one per amplifier.
+    pub fn candidates(&self) -> Vec<Vec<isize>> {
+        let mut candidates = vec![];
+        let mut used = vec![false; self.phases.len()];
+        let mut current = vec![];
+        self.extend_candidates(&mut used, &mut current, &mut candidates);
+        candidates
+    }
+
+    fn extend_candidates(
+        &self,
+        used: &mut Vec<bool>,
+        current: &mut Vec<isize>,
+        candidates: &mut Vec<Vec<isize>>,
+    ) {
+        if current.len() == self.programs.len() {
+            candidates.push(current.clone());
+            return;
+        }
+        for index in 0..self.phases.len() {
+            if !used[index] {
+                used[index] = true;
+                current.push(self.phases[index]);
+                self.extend_candidates(used, current, candidates);
+                current.pop();
+                used[index] = false;
+            }
+        }
+    }
+
+    /// The signal given by running the amplifiers with a phase each.
+    pub fn signal(&self, phases: &[isize]) -> Result<isize> {
+        self.signal_with(&self.topology.wiring(self.programs.len())?, phases)
+    }
+
+    fn signal_with(&self, wiring: &Wiring, phases: &[isize]) -> Result<isize> {
+        if phases.len() != self.programs.len() {
+            return Err(anyhow!(
+                "expected {} phases, got {}",
+                self.programs.len(),
+                phases.len()
+            ));
+        }
+        let mut progs = self.programs.clone();
+        let mut queues: Vec<VecDeque<isize>> = phases.iter().map(|&p| vec![p].into()).collect();
+        for &source in &wiring.sources {
+            queues[source].push_back(self.input);
+        }
+        let mut exited = vec![false; progs.len()];
+        let mut last = None;
+
+        // Keep running amplifiers with waiting input until none are left
+        let mut progress = true;
+        while progress {
+            progress = false;
+            for amp in 0..progs.len() {
+                if exited[amp] || queues[amp].is_empty() {
+                    continue;
+                }
+                progress = true;
+                let inputs: Vec<isize> = queues[amp].drain(..).collect();
+                let halt = run(&mut progs[amp], &inputs)?;
+                for &o in &halt.output {
+                    for &to in &wiring.outputs[amp] {
+                        queues[to].push_back(o);
+                    }
+                }
+                if amp == wiring.sink {
+                    last = halt.output.last().cloned().or(last);
+                }
+                exited[amp] = halt.cause == HaltCause::Exit;
+            }
+        }
+        last.ok_or_else(|| anyhow!("amplifier {} gave no signal", wiring.sink))
+    }
+
+    /// Tries every candidate in parallel, preferring the earliest candidate
+    /// when signals tie.
+    pub fn best(&self) -> Result<Best> {
+        let wiring = self.topology.wiring(self.programs.len())?;
+        let results = self
+            .candidates()
+            .into_par_iter()
+            .map(|phases| Ok((self.signal_with(&wiring, &phases)?, phases)))
+            .collect::<Result<Vec<(isize, Vec<isize>)>>>()?;
+        let mut best: Option<Best> = None;
+        for (signal, phases) in results {
+            if best
+                .as_ref()
+                .is_none_or(|b| self.objective.better(signal, b.signal))
+            {
+                best = Some(Best { phases, signal });
+            }
+        }
+        best.ok_or_else(|| {
+            anyhow!(
+                "{} phases can't be shared between {} amplifiers",
+                self.phases.len(),
+                self.programs.len()
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search(memory: Vec<isize>) -> Search {
+        Search::new(Program::with_memory(memory), 5)
+    }
+
+    #[test]
+    fn test_chain() -> Result<()> {
+        let s = search(vec![
+            3, 15, 3, 16, 1002, 16, 10, 16, 1, 16, 15, 15, 4, 15, 99, 0, 0,
+        ]);
+        assert_eq!(s.signal(&[4, 3, 2, 1, 0])?, 43210);
+        assert_eq!(
+            s.best()?,
+            Best {
+                phases: vec![4, 3, 2, 1, 0],
+                signal: 43210
+            }
+        );
+
+        let s = search(vec![
+            3, 23, 3, 24, 1002, 24, 10, 24, 1002, 23, -1, 23, 101, 5, 23, 23, 1, 24, 23, 23, 4, 23,
+            99, 0, 0,
+        ]);
+        assert_eq!(s.best()?.phases, vec![0, 1, 2, 3, 4]);
+        assert_eq!(s.best()?.signal, 54321);
+
+        let s = search(vec![
+            3, 31, 3, 32, 1002, 32, 10, 32, 1001, 31, -2, 31, 1007, 31, 0, 33, 1002, 33, 7, 33, 1,
+            33, 31, 31, 1, 32, 31, 31, 4, 31, 99, 0, 0, 0,
+        ]);
+        assert_eq!(s.best()?.phases, vec![1, 0, 4, 3, 2]);
+        assert_eq!(s.best()?.signal, 65210);
+        Ok(())
+    }
+
+    #[test]
+    fn test_feedback() -> Result<()> {
+        let s = search(vec![
+            3, 26, 1001, 26, -4, 26, 3, 27, 1002, 27, 2, 27, 1, 27, 26, 27, 4, 27, 1001, 28, -1,
+            28, 1005, 28, 6, 99, 0, 0, 5,
+        ])
+        .topology(Topology::Feedback)
+        .phases(&[5, 6, 7, 8, 9]);
+        assert_eq!(
+            s.best()?,
+            Best {
+                phases: vec![9, 8, 7, 6, 5],
+                signal: 139629729
+            }
+        );
+
+        let s = search(vec![
+            3, 52, 1001, 52, -5, 52, 3, 53, 1, 52, 56, 54, 1007, 54, 5, 55, 1005, 55, 26, 1001, 54,
+            -5, 54, 1105, 1, 12, 1, 53, 54, 53, 1008, 54, 0, 55, 1001, 55, 1, 55, 2, 53, 55, 53, 4,
+            53, 1001, 56, -1, 56, 1005, 56, 6, 99, 0, 0, 0, 0, 10,
+        ])
+        .topology(Topology::Feedback)
+        .phases(&[5, 6, 7, 8, 9]);
+        assert_eq!(s.signal(&[9, 7, 8, 5, 6])?, 18216);
+        assert_eq!(s.best()?.signal, 18216);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dag() -> Result<()> {
+        // Outputs phase + input
+        let add = Program::with_memory(vec![3, 11, 3, 12, 1, 11, 12, 13, 4, 13, 99, 0, 0, 0]);
+        // Outputs phase + a * b
+        let mut merge = vec![3, 20, 3, 21, 3, 22, 2, 21, 22, 23, 1, 23, 20, 23, 4, 23, 99];
+        merge.resize(24, 0);
+        let merge = Program::with_memory(merge);
+
+        let diamond = Topology::Dag(vec![(0, 1), (0, 2), (1, 3), (2, 3)]);
+        let s = Search::with_programs(vec![add.clone(), add.clone(), add.clone(), merge])
+            .topology(diamond);
+        assert_eq!(s.signal(&[3, 2, 1, 0])?, 20);
+        assert_eq!(
+            s.best()?,
+            Best {
+                phases: vec![3, 1, 2, 0],
+                signal: 20
+            }
+        );
+        let s = s.objective(Objective::Minimise);
+        assert_eq!(
+            s.best()?,
+            Best {
+                phases: vec![0, 1, 2, 3],
+                signal: 5
+            }
+        );
+
+        let s = Search::new(add, 2);
+        assert!(s.signal(&[0]).is_err());
+        let s = s.topology(Topology::Dag(vec![(0, 1), (1, 0)]));
+        assert!(s.best().is_err());
+        let s = s.topology(Topology::Dag(vec![]));
+        assert!(s.best().is_err());
+        let s = s.topology(Topology::Dag(vec![(0, 2)]));
+        assert!(s.best().is_err());
+        let s = s.topology(Topology::Chain).phases(&[1]);
+        assert!(s.best().is_err());
+        Ok(())
+    }
+}
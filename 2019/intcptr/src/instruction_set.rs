@@ -0,0 +1,184 @@
+use anyhow::{anyhow, Result};
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::{OpCode, Param, Program};
+
+// Op codes are the lowest two digits of an instruction, the rest are param
+// modes.
+const MAX_OP_CODES: usize = 100;
+
+pub type OpHandler = dyn Fn(&mut OpContext<'_>) -> Result<()> + Send + Sync;
+
+#[derive(Clone)]
+pub(crate) struct CustomOp {
+    pub(crate) args: usize,
+    pub(crate) handler: Arc<OpHandler>,
+}
+
+#[derive(Clone)]
+pub(crate) enum OpDef {
+    Builtin(OpCode),
+    Custom(CustomOp),
+}
+
+impl OpDef {
+    pub(crate) fn args(&self) -> usize {
+        match self {
+            OpDef::Builtin(code) => code.args(),
+            OpDef::Custom(op) => op.args,
+        }
+    }
+}
+
+/// The set of op codes a program is allowed to execute, along with any custom
+/// op codes and their handlers.
+#[derive(Clone)]
+pub struct InstructionSet {
+    ops: Vec<Option<OpDef>>,
+}
+
+impl InstructionSet {
+    pub fn empty() -> Self {
+        Self {
+            ops: vec![None; MAX_OP_CODES],
+        }
+    }
+
+    /// Add, mul and exit, as used by the day 2 programs.
+    pub fn day2() -> Self {
+        Self::with_builtins(&[OpCode::Add, OpCode::Mul, OpCode::Exit])
+    }
+
+    /// Day 2 plus input, output, jumps and comparisons, as used by the day 5
+    /// programs.
+    pub fn day5() -> Self {
+        Self::with_builtins(&[
+            OpCode::Add,
+            OpCode::Mul,
+            OpCode::Input,
+            OpCode::Output,
+            OpCode::JumpIfTrue,
+            OpCode::JumpIfFalse,
+            OpCode::LessThan,
+            OpCode::Equals,
+            OpCode::Exit,
+        ])
+    }
+
+    /// Every op code up to and including day 9's relative base.
+    pub fn full() -> Self {
+        Self::with_builtins(&OpCode::variants())
+    }
+
+    fn with_builtins(codes: &[OpCode]) -> Self {
+        let mut set = Self::empty();
+        for code in codes {
+            set.ops[code.code() as usize] = Some(OpDef::Builtin(*code));
+        }
+        set
+    }
+
+    /// Registers a custom op code which takes `args` params. The handler is
+    /// called with a context giving access to the params and the program, and
+    /// unless it jumps the program moves on to the next instruction.
+    pub fn register<F>(&mut self, code: isize, args: usize, handler: F) -> Result<()>
+    where
+        F: Fn(&mut OpContext<'_>) -> Result<()> + Send + Sync + 'static,
+    {
+        if code < 0 || code as usize >= MAX_OP_CODES {
+            return Err(anyhow!("op code {} out of range", code));
+        }
+        if self.contains(code) {
+            return Err(anyhow!("op code {} already registered", code));
+        }
+        self.ops[code as usize] = Some(OpDef::Custom(CustomOp {
+            args,
+            handler: Arc::new(handler),
+        }));
+        Ok(())
+    }
+
+    pub fn remove(&mut self, code: isize) {
+        if let Some(op) = self.ops.get_mut(code as usize) {
+            *op = None;
+        }
+    }
+
+    pub fn contains(&self, code: isize) -> bool {
+        self.lookup(code).is_some()
+    }
+
+    pub(crate) fn lookup(&self, code: isize) -> Option<&OpDef> {
+        if code < 0 {
+            return None;
+        }
+        self.ops.get(code as usize).and_then(|op| op.as_ref())
+    }
+}
+
+impl Default for InstructionSet {
+    fn default() -> Self {
+        Self::full()
+    }
+}
+
+impl fmt::Debug for InstructionSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(
+                self.ops
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, op)| op.is_some())
+                    .map(|(code, _)| code),
+            )
+            .finish()
+    }
+}
+
+/// Gives custom op handlers access to their params and the running program.
+pub struct OpContext<'a> {
+    pub(crate) prog: &'a mut Program,
+    pub(crate) params: &'a [Param],
+    pub(crate) output: &'a mut Vec<isize>,
+    pub(crate) jump: Option<usize>,
+    pub(crate) exit: bool,
+}
+
+impl<'a> OpContext<'a> {
+    fn param(&self, index: usize) -> Result<&Param> {
+        self.params
+            .get(index)
+            .ok_or_else(|| anyhow!("param {} out of range", index))
+    }
+
+    /// Resolves the value of a param according to its mode.
+    pub fn arg(&self, index: usize) -> Result<isize> {
+        self.param(index)?.resolve_value(self.prog)
+    }
+
+    /// Writes to the address a param points to.
+    pub fn set_arg(&mut self, index: usize, val: isize) -> Result<()> {
+        let addr = self.param(index)?.addr(self.prog)?;
+        self.prog.set(addr, val);
+        Ok(())
+    }
+
+    pub fn output(&mut self, val: isize) {
+        self.output.push(val);
+    }
+
+    pub fn jump(&mut self, addr: usize) {
+        self.jump = Some(addr);
+    }
+
+    pub fn exit(&mut self) {
+        self.exit = true;
+    }
+
+    pub fn program(&mut self) -> &mut Program {
+        self.prog
+    }
+}
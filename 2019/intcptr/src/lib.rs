@@ -1,5 +1,14 @@
 use anyhow::{anyhow, Context, Result};
 
+use std::sync::Arc;
+
+pub mod amplifier;
+mod instruction_set;
+pub mod noun_verb;
+
+use instruction_set::{CustomOp, OpDef};
+pub use instruction_set::{InstructionSet, OpContext, OpHandler};
+
 #[derive(Copy, Clone, Debug)]
 enum Param {
     Position(usize),
@@ -19,7 +28,7 @@ impl Param {
         match self {
             Self::Position(at) => Ok(*at),
             Self::Relative(at) => {
-                let addr = *at + prog.relative_base as isize;
+                let addr = *at + prog.relative_base;
                 if addr < 0 {
                     return Err(anyhow!("negative address"));
                 }
@@ -30,7 +39,6 @@ impl Param {
     }
 }
 
-#[derive(Debug)]
 enum Instruction {
     Add { x: Param, y: Param, dest: Param },
     Mul { x: Param, y: Param, dest: Param },
@@ -42,14 +50,15 @@ enum Instruction {
     Equals { x: Param, y: Param, dest: Param },
     RelativeBase { base: Param },
     Exit,
+    Custom { op: CustomOp, params: Vec<Param> },
 }
 
 impl Instruction {
-    fn parse(input: &[isize], at: usize) -> Result<Self> {
+    fn parse(input: &[isize], at: usize, instruction_set: &InstructionSet) -> Result<Self> {
         if at >= input.len() {
             return Err(anyhow!("at out of range"));
         }
-        let op = Op::parse(input[at]).context("failed to parse op")?;
+        let op = Op::parse(input[at], instruction_set).context("failed to parse op")?;
         let params: Vec<Param> = op
             .param_modes
             .iter()
@@ -63,7 +72,11 @@ impl Instruction {
                 }
             })
             .collect();
-        Ok(match op.code {
+        let code = match op.def {
+            OpDef::Builtin(code) => code,
+            OpDef::Custom(op) => return Ok(Instruction::Custom { op, params }),
+        };
+        Ok(match code {
             OpCode::Add => Instruction::Add {
                 x: params[0],
                 y: params[1],
@@ -100,6 +113,7 @@ impl Instruction {
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 enum OpCode {
     Add,
     Mul,
@@ -114,20 +128,34 @@ enum OpCode {
 }
 
 impl OpCode {
-    fn parse(input: isize) -> Result<Self> {
-        Ok(match input {
-            1 => OpCode::Add,
-            2 => OpCode::Mul,
-            3 => OpCode::Input,
-            4 => OpCode::Output,
-            5 => OpCode::JumpIfTrue,
-            6 => OpCode::JumpIfFalse,
-            7 => OpCode::LessThan,
-            8 => OpCode::Equals,
-            9 => OpCode::RelativeBase,
-            99 => OpCode::Exit,
-            _ => return Err(anyhow!("invalid opcode")),
-        })
+    fn variants() -> Vec<Self> {
+        vec![
+            OpCode::Add,
+            OpCode::Mul,
+            OpCode::Input,
+            OpCode::Output,
+            OpCode::JumpIfTrue,
+            OpCode::JumpIfFalse,
+            OpCode::LessThan,
+            OpCode::Equals,
+            OpCode::RelativeBase,
+            OpCode::Exit,
+        ]
+    }
+
+    fn code(self) -> isize {
+        match self {
+            OpCode::Add => 1,
+            OpCode::Mul => 2,
+            OpCode::Input => 3,
+            OpCode::Output => 4,
+            OpCode::JumpIfTrue => 5,
+            OpCode::JumpIfFalse => 6,
+            OpCode::LessThan => 7,
+            OpCode::Equals => 8,
+            OpCode::RelativeBase => 9,
+            OpCode::Exit => 99,
+        }
     }
 
     fn args(&self) -> usize {
@@ -158,17 +186,21 @@ impl ParamMode {
 }
 
 struct Op {
-    code: OpCode,
+    def: OpDef,
     param_modes: Vec<ParamMode>,
 }
 
 impl Op {
-    fn parse(input: isize) -> Result<Self> {
+    fn parse(input: isize, instruction_set: &InstructionSet) -> Result<Self> {
         let mut op_iter = OpIter::new(input);
-        let code = OpCode::parse(op_iter.next().ok_or_else(|| anyhow!("invalid op code"))?)?;
-        let num_args = code.args();
+        let code = op_iter.next().ok_or_else(|| anyhow!("invalid op code"))?;
+        let def = instruction_set
+            .lookup(code)
+            .cloned()
+            .ok_or_else(|| anyhow!("invalid opcode {}", code))?;
+        let num_args = def.args();
         Ok(Self {
-            code,
+            def,
             param_modes: op_iter
                 .take(num_args)
                 .map(ParamMode::parse)
@@ -226,14 +258,20 @@ pub struct Program {
     pub memory: Vec<isize>,
     pub pos: usize,
     pub relative_base: isize,
+    pub instruction_set: Arc<InstructionSet>,
 }
 
 impl Program {
     pub fn with_memory(memory: Vec<isize>) -> Program {
+        Program::with_instruction_set(memory, InstructionSet::default())
+    }
+
+    pub fn with_instruction_set(memory: Vec<isize>, instruction_set: InstructionSet) -> Program {
         Program {
             memory,
             pos: 0,
             relative_base: 0,
+            instruction_set: Arc::new(instruction_set),
         }
     }
 
@@ -259,7 +297,7 @@ pub fn run(prog: &mut Program, inputs: &[isize]) -> Result<Halt> {
     let mut input_iter = inputs.iter();
     let mut output: Vec<isize> = vec![];
     loop {
-        match Instruction::parse(&prog.memory, prog.pos)? {
+        match Instruction::parse(&prog.memory, prog.pos, &prog.instruction_set)? {
             Instruction::Add { x, y, dest } => {
                 prog.set(
                     dest.addr(prog)?,
@@ -332,6 +370,27 @@ pub fn run(prog: &mut Program, inputs: &[isize]) -> Result<Halt> {
                     output,
                 })
             }
+            Instruction::Custom { op, params } => {
+                let mut ctx = OpContext {
+                    prog,
+                    params: &params,
+                    output: &mut output,
+                    jump: None,
+                    exit: false,
+                };
+                (op.handler)(&mut ctx)?;
+                let (jump, exit) = (ctx.jump, ctx.exit);
+                if exit {
+                    return Ok(Halt {
+                        cause: HaltCause::Exit,
+                        output,
+                    });
+                }
+                match jump {
+                    Some(addr) => prog.pos = addr,
+                    None => prog.pos += op.args + 1,
+                }
+            }
         }
     }
 }
@@ -444,4 +503,50 @@ mod tests {
         assert_eq!(run(&mut prog, &[])?.output, vec![1125899906842624]);
         Ok(())
     }
+
+    #[test]
+    fn test_restricted_instruction_set() -> Result<()> {
+        let mem = vec![1, 0, 0, 0, 99];
+        let mut prog = Program::with_instruction_set(mem, InstructionSet::day2());
+        run(&mut prog, &[])?;
+        assert_eq!(prog.memory, vec![2, 0, 0, 0, 99]);
+
+        // Input isn't available until day 5
+        let mem = vec![3, 0, 99];
+        let mut prog = Program::with_instruction_set(mem.clone(), InstructionSet::day2());
+        assert!(run(&mut prog, &[1]).is_err());
+        let mut prog = Program::with_instruction_set(mem, InstructionSet::day5());
+        run(&mut prog, &[1])?;
+        assert_eq!(prog.memory, vec![1, 0, 99]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_custom_op() -> Result<()> {
+        let mut set = InstructionSet::full();
+        // Subtract
+        set.register(10, 3, |ctx| {
+            let val = ctx.arg(0)? - ctx.arg(1)?;
+            ctx.set_arg(2, val)
+        })?;
+        // Output the param then exit
+        set.register(11, 1, |ctx| {
+            let val = ctx.arg(0)?;
+            ctx.output(val);
+            ctx.exit();
+            Ok(())
+        })?;
+        let mut prog = Program::with_instruction_set(vec![1110, 9, 4, 5, 11, 0, 99], set);
+        assert_eq!(run(&mut prog, &[])?.output, vec![5]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_register_conflict() {
+        let mut set = InstructionSet::full();
+        assert!(set.register(1, 3, |_| Ok(())).is_err());
+        assert!(set.register(100, 0, |_| Ok(())).is_err());
+        set.remove(1);
+        assert!(set.register(1, 3, |_| Ok(())).is_ok());
+    }
 }
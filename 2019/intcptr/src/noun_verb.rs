@@ -0,0 +1,263 @@
+use anyhow::{anyhow, Result};
+use rayon::prelude::*;
+
+use std::ops::RangeInclusive;
+
+use crate::{run, Program};
+
+const NOUN: usize = 1;
+const VERB: usize = 2;
+const OUTPUT: usize = 0;
+
+/// How a solution was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// Solved directly, the output being linear in the noun and verb.
+    Linear,
+    BruteForce,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Solution {
+    pub noun: isize,
+    pub verb: isize,
+    pub method: Method,
+}
+
+impl Solution {
+    /// The puzzle answer, `100 * noun + verb`.
+    pub fn answer(&self) -> isize {
+        100 * self.noun + self.verb
+    }
+}
+
+/// The output at address 0 as `base + noun * noun_step + verb * verb_step`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Linear {
+    pub base: isize,
+    pub noun_step: isize,
+    pub verb_step: isize,
+}
+
+impl Linear {
+    pub fn output(&self, noun: isize, verb: isize) -> isize {
+        self.base + noun * self.noun_step + verb * self.verb_step
+    }
+}
+
+/// Finds the noun and verb to put in addresses 1 and 2 for a program to
+/// leave a target at address 0.
+pub struct Solver {
+    program: Program,
+    range: RangeInclusive<isize>,
+    brute_force: bool,
+}
+
+impl Solver {
+    /// Defaults to nouns and verbs from 0 to 99.
+    pub fn new(program: Program) -> Self {
+        Self {
+            program,
+            range: 0..=99,
+            brute_force: false,
+        }
+    }
+
+    /// The values both the noun and verb can take.
+    pub fn range(mut self, range: RangeInclusive<isize>) -> Self {
+        self.range = range;
+        self
+    }
+
+    /// Skips trying to solve directly.
+    pub fn brute_force(mut self) -> Self {
+        self.brute_force = true;
+        self
+    }
+
+    /// The output for a noun and verb.
+    pub fn output(&self, noun: isize, verb: isize) -> Result<isize> {
+        let mut prog = self.program.clone();
+        prog.set(NOUN, noun);
+        prog.set(VERB, verb);
+        run(&mut prog, &[])?;
+        Ok(prog.get(OUTPUT))
+    }
+
+    /// Fits a linear model from the first values in the range, returning it
+    /// if it also predicts the corners and middle of the range. This can be
+    /// fooled, so neither its solutions nor its lack of one can be trusted
+    /// without checking.
+    pub fn linear(&self) -> Option<Linear> {
+        let (start, end) = (*self.range.start(), *self.range.end());
+        if end <= start {
+            return None;
+        }
+        let base_output = self.output(start, start).ok()?;
+        let noun_step = self.output(start + 1, start).ok()? - base_output;
+        let verb_step = self.output(start, start + 1).ok()? - base_output;
+        let model = Linear {
+            base: base_output - start * noun_step - start * verb_step,
+            noun_step,
+            verb_step,
+        };
+        let mid = start + (end - start) / 2;
+        let samples = [(end, start), (start, end), (end, end), (mid, mid + 1)];
+        if samples
+            .iter()
+            .all(|&(n, v)| self.output(n, v).ok() == Some(model.output(n, v)))
+        {
+            Some(model)
+        } else {
+            None
+        }
+    }
+
+    /// The lowest noun, then verb, giving the target. A solution from the
+    /// linear model is used once it's checked, otherwise every pair is tried.
+    pub fn solve(&self, target: isize) -> Result<Option<Solution>> {
+        if self.range.is_empty() {
+            return Err(anyhow!("empty range {:?}", self.range));
+        }
+        if !self.brute_force {
+            if let Some(solution) = self.linear().and_then(|m| self.solve_linear(&m, target)) {
+                if self.output(solution.noun, solution.verb)? == target {
+                    return Ok(Some(solution));
+                }
+            }
+        }
+        Ok(self.solve_brute_force(target))
+    }
+
+    fn solve_linear(&self, model: &Linear, target: isize) -> Option<Solution> {
+        let remaining = target - model.base;
+        self.range.clone().find_map(|noun| {
+            let rest = remaining - noun * model.noun_step;
+            let verb = if model.verb_step == 0 {
+                if rest != 0 {
+                    return None;
+                }
+                *self.range.start()
+            } else if rest % model.verb_step == 0 {
+                rest / model.verb_step
+            } else {
+                return None;
+            };
+            if self.range.contains(&verb) {
+                Some(Solution {
+                    noun,
+                    verb,
+                    method: Method::Linear,
+                })
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Tries every pair in parallel. Pairs which make the program fail can't
+    /// give the target, so are skipped.
+    fn solve_brute_force(&self, target: isize) -> Option<Solution> {
+        self.range.clone().into_par_iter().find_map_first(|noun| {
+            self.range
+                .clone()
+                .find_map(|verb| match self.output(noun, verb) {
+                    Ok(output) if output == target => Some(Solution {
+                        noun,
+                        verb,
+                        method: Method::BruteForce,
+                    }),
+                    _ => None,
+                })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Outputs 5 * (noun + verb) + 7
+    const LINEAR: [isize; 19] = [1, 0, 0, 3, 1, 1, 2, 3, 2, 3, 17, 0, 1, 0, 18, 0, 99, 5, 7];
+    // Outputs (noun + verb)^2 + 7
+    const SQUARE: [isize; 19] = [1, 0, 0, 3, 1, 1, 2, 3, 2, 3, 3, 0, 1, 0, 18, 0, 99, 5, 7];
+
+    // Outputs 5 * (noun + verb) + 7, plus 1 when the noun is 3 and the verb
+    // is 4, which the linear model doesn't sample
+    const FOOLING: [isize; 39] = [
+        1, 0, 0, 3, 1, 1, 2, 3, 2, 3, 33, 0, 1, 0, 34, 0, 8, 1, 35, 37, 8, 2, 36, 38, 2, 37, 38,
+        37, 1, 0, 37, 0, 99, 5, 7, 3, 4, 0, 0,
+    ];
+
+    fn solver(memory: &[isize]) -> Solver {
+        Solver::new(Program::with_memory(memory.to_vec())).range(0..=18)
+    }
+
+    #[test]
+    fn test_linear() -> Result<()> {
+        let s = solver(&LINEAR);
+        assert_eq!(s.output(2, 3)?, 32);
+        assert_eq!(
+            s.linear(),
+            Some(Linear {
+                base: 7,
+                noun_step: 5,
+                verb_step: 5
+            })
+        );
+        let solution = Solution {
+            noun: 0,
+            verb: 10,
+            method: Method::Linear,
+        };
+        assert_eq!(s.solve(57)?, Some(solution));
+        assert_eq!(solution.answer(), 10);
+        assert_eq!(s.solve(182)?.map(|s| (s.noun, s.verb)), Some((17, 18)));
+        // The model having no solution is checked by brute force
+        assert_eq!(s.solve_linear(&s.linear().unwrap(), 58), None);
+        assert_eq!(s.solve(58)?, None);
+
+        let s = s.brute_force();
+        assert_eq!(
+            s.solve(57)?,
+            Some(Solution {
+                method: Method::BruteForce,
+                ..solution
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_fooled_linear() -> Result<()> {
+        let s = solver(&FOOLING);
+        assert_eq!(s.output(3, 4)?, 43);
+        assert!(s.linear().is_some());
+        // The model has no solution, but brute force finds the one it missed
+        assert_eq!(
+            s.solve(43)?,
+            Some(Solution {
+                noun: 3,
+                verb: 4,
+                method: Method::BruteForce,
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_non_linear() -> Result<()> {
+        let s = solver(&SQUARE);
+        assert_eq!(s.linear(), None);
+        assert_eq!(
+            s.solve(88)?,
+            Some(Solution {
+                noun: 0,
+                verb: 9,
+                method: Method::BruteForce,
+            })
+        );
+        assert_eq!(s.solve(9)?, None);
+        Ok(())
+    }
+}
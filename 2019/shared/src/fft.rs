@@ -0,0 +1,66 @@
+use anyhow::{anyhow, Result};
+
+/// Parses a signal of single digits.
+pub fn digits(input: &str) -> Result<Vec<i32>> {
+    input
+        .chars()
+        .map(|c| {
+            c.to_digit(10)
+                .map(|d| d as i32)
+                .ok_or_else(|| anyhow!("'{}' is not a digit", c))
+        })
+        .collect()
+}
+
+/// Applies a phase to the part of a signal from `start` onwards, which is all
+/// that's needed as each output digit only depends on the digits at or after
+/// it. Each output digit is the sum of runs of the pattern, which are read
+/// from prefix sums, so the whole phase is O(n log n).
+pub fn phase(signal: &[i32], start: usize) -> Vec<i32> {
+    let mut prefix = vec![0i32; signal.len() + 1];
+    for (i, v) in signal.iter().enumerate() {
+        prefix[i + 1] = prefix[i] + v;
+    }
+    let sum =
+        |from: usize, to: usize| prefix[to.min(signal.len())] - prefix[from.min(signal.len())];
+    (0..signal.len())
+        .map(|i| {
+            let n = start + i + 1;
+            let mut total = 0;
+            // The first run of ones starts at the digit itself
+            let mut from = i;
+            while from < signal.len() {
+                total += sum(from, from + n);
+                total -= sum(from + 2 * n, from + 3 * n);
+                from += 4 * n;
+            }
+            (total % 10).abs()
+        })
+        .collect()
+}
+
+/// Applies `n` phases to the part of a signal from `start` onwards.
+pub fn fft(signal: &[i32], start: usize, n: usize) -> Vec<i32> {
+    let mut signal = signal.to_vec();
+    for _ in 0..n {
+        signal = phase(&signal, start);
+    }
+    signal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fft() -> Result<()> {
+        assert_eq!(
+            fft(&[1, 2, 3, 4, 5, 6, 7, 8], 0, 4),
+            vec![0, 1, 0, 2, 9, 4, 9, 8]
+        );
+        let input = digits("80871224585914546619083218645595")?;
+        assert_eq!(fft(&input, 0, 100)[..8], [2, 4, 1, 7, 6, 1, 7, 6]);
+        assert!(digits("12a4").is_err());
+        Ok(())
+    }
+}
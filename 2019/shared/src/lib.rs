@@ -0,0 +1,4 @@
+pub mod fft;
+pub mod reaction;
+pub mod sif;
+pub mod wire;
@@ -0,0 +1,678 @@
+use anyhow::Result;
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::iter;
+
+use helper::scan;
+
+pub const ORE: &str = "ORE";
+pub const FUEL: &str = "FUEL";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChemicalAmount {
+    pub name: String,
+    pub count: usize,
+}
+
+impl ChemicalAmount {
+    pub fn parse(input: &str) -> Result<Self> {
+        let (count, name) = scan!("{} {}", usize, String)(input)?;
+        Ok(ChemicalAmount { name, count })
+    }
+
+    pub fn parse_many(input: &str) -> Result<Vec<Self>> {
+        scan::comma_list(input, ChemicalAmount::parse)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reaction {
+    pub input: Vec<ChemicalAmount>,
+    pub output: ChemicalAmount,
+}
+
+impl Reaction {
+    pub fn parse(input: &str) -> Result<Self> {
+        let (input, output) = scan::parse_pair(
+            input,
+            "=>",
+            ChemicalAmount::parse_many,
+            ChemicalAmount::parse,
+        )?;
+        Ok(Reaction { input, output })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reactions(pub HashMap<String, Reaction>);
+
+impl Reactions {
+    pub fn parse(input: &str) -> Result<Self> {
+        let list = input
+            .trim()
+            .lines()
+            .map(Reaction::parse)
+            .collect::<Result<Vec<Reaction>>>()?;
+
+        let mut reactions: HashMap<String, Reaction> = HashMap::new();
+        for r in list {
+            let name = r.output.name.to_owned();
+            if reactions.insert(name.to_owned(), r).is_some() {
+                return Err(GraphError::DuplicateRecipe(name).into());
+            }
+        }
+        Ok(Reactions(reactions))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphError {
+    UnknownChemical(String),
+    MissingRecipe(String),
+    DuplicateRecipe(String),
+    ZeroOutput(String),
+    Cycle(Vec<String>),
+    // The target can be made without any of the source
+    Unlimited { source: String, target: String },
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::UnknownChemical(name) => write!(f, "unknown chemical {}", name),
+            GraphError::MissingRecipe(name) => write!(f, "no reaction produces {}", name),
+            GraphError::DuplicateRecipe(name) => {
+                write!(f, "more than one reaction produces {}", name)
+            }
+            GraphError::ZeroOutput(name) => write!(f, "reaction produces no {}", name),
+            GraphError::Cycle(names) => write!(f, "reactions form a cycle: {}", names.join(" => ")),
+            GraphError::Unlimited { source, target } => {
+                write!(f, "{} needs no {}, so there's no limit", target, source)
+            }
+        }
+    }
+}
+
+impl Error for GraphError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Recipe {
+    count: usize,
+    inputs: Vec<(usize, usize)>,
+}
+
+/// The amount of a source chemical needed to make a target, and what's left
+/// over from reactions which made more than was needed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Requirements {
+    pub source: usize,
+    pub leftovers: BTreeMap<String, usize>,
+}
+
+/// Reactions with chemical names interned to indices, in an order where
+/// every chemical comes before the chemicals it's made from. Needs can then
+/// be worked out in one pass, as everything using a chemical has already
+/// asked for it by the time it's reached.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Graph {
+    names: Vec<String>,
+    ids: HashMap<String, usize>,
+    recipes: Vec<Option<Recipe>>,
+    order: Vec<usize>,
+}
+
+impl Graph {
+    pub fn new(reactions: &Reactions) -> Result<Self, GraphError> {
+        let names: Vec<String> = reactions
+            .0
+            .values()
+            .flat_map(|r| r.input.iter().chain(iter::once(&r.output)))
+            .map(|c| c.name.to_owned())
+            .collect::<BTreeSet<String>>()
+            .into_iter()
+            .collect();
+        let ids: HashMap<String, usize> = names
+            .iter()
+            .enumerate()
+            .map(|(id, name)| (name.to_owned(), id))
+            .collect();
+        let mut recipes = vec![None; names.len()];
+        for r in reactions.0.values() {
+            if r.output.count == 0 {
+                return Err(GraphError::ZeroOutput(r.output.name.to_owned()));
+            }
+            recipes[ids[&r.output.name]] = Some(Recipe {
+                count: r.output.count,
+                inputs: r.input.iter().map(|c| (ids[&c.name], c.count)).collect(),
+            });
+        }
+        let mut graph = Graph {
+            names,
+            ids,
+            recipes,
+            order: vec![],
+        };
+        graph.order = graph.topological_order()?;
+        Ok(graph)
+    }
+
+    fn id(&self, name: &str) -> Result<usize, GraphError> {
+        self.ids
+            .get(name)
+            .copied()
+            .ok_or_else(|| GraphError::UnknownChemical(name.to_string()))
+    }
+
+    fn topological_order(&self) -> Result<Vec<usize>, GraphError> {
+        #[derive(Copy, Clone, PartialEq, Eq)]
+        enum State {
+            New,
+            Visiting,
+            Done,
+        }
+
+        // Depth first, each chemical being added after all it's made from
+        fn visit(
+            graph: &Graph,
+            id: usize,
+            states: &mut Vec<State>,
+            path: &mut Vec<usize>,
+            order: &mut Vec<usize>,
+        ) -> Result<(), GraphError> {
+            match states[id] {
+                State::Done => return Ok(()),
+                State::Visiting => {
+                    let from = path.iter().position(|&p| p == id).unwrap_or(0);
+                    return Err(GraphError::Cycle(
+                        path[from..]
+                            .iter()
+                            .chain(iter::once(&id))
+                            .map(|&p| graph.names[p].to_owned())
+                            .collect(),
+                    ));
+                }
+                State::New => {}
+            }
+            states[id] = State::Visiting;
+            path.push(id);
+            if let Some(recipe) = &graph.recipes[id] {
+                for &(input, _) in &recipe.inputs {
+                    visit(graph, input, states, path, order)?;
+                }
+            }
+            path.pop();
+            states[id] = State::Done;
+            order.push(id);
+            Ok(())
+        }
+
+        let mut states = vec![State::New; self.names.len()];
+        let mut order = vec![];
+        for id in 0..self.names.len() {
+            visit(self, id, &mut states, &mut vec![], &mut order)?;
+        }
+        order.reverse();
+        Ok(order)
+    }
+
+    /// Works out how much of `source` is needed to make `n` of `target`,
+    /// everything else having to be made by reactions.
+    pub fn requirements(
+        &self,
+        source: &str,
+        target: &str,
+        n: usize,
+    ) -> Result<Requirements, GraphError> {
+        let source = self.id(source)?;
+        let target = self.id(target)?;
+        let mut needed = vec![0usize; self.names.len()];
+        let mut leftovers = BTreeMap::new();
+        needed[target] = n;
+        for &id in &self.order {
+            if id == source || needed[id] == 0 {
+                continue;
+            }
+            let recipe = self.recipes[id]
+                .as_ref()
+                .ok_or_else(|| GraphError::MissingRecipe(self.names[id].to_owned()))?;
+            let times = needed[id].div_ceil(recipe.count);
+            let leftover = times * recipe.count - needed[id];
+            if leftover > 0 {
+                leftovers.insert(self.names[id].to_owned(), leftover);
+            }
+            for &(input, count) in &recipe.inputs {
+                needed[input] += times * count;
+            }
+        }
+        Ok(Requirements {
+            source: needed[source],
+            leftovers,
+        })
+    }
+
+    /// The most of `target` that can be made from `available` of `source`.
+    pub fn max_target(
+        &self,
+        source: &str,
+        target: &str,
+        available: usize,
+    ) -> Result<usize, GraphError> {
+        let per_one = self.requirements(source, target, 1)?.source;
+        if per_one == 0 {
+            return Err(GraphError::Unlimited {
+                source: source.to_string(),
+                target: target.to_string(),
+            });
+        }
+        // Leftovers mean making one at a time is the worst case, so there's
+        // a lower bound to search up from
+        let mut lower = available / per_one;
+        let mut upper = lower.max(1) * 2;
+        while self.requirements(source, target, upper)?.source <= available {
+            lower = upper;
+            upper *= 2;
+        }
+        // `lower` can always be made and `upper` never can
+        while upper - lower > 1 {
+            let mid = (lower + upper) / 2;
+            if self.requirements(source, target, mid)?.source <= available {
+                lower = mid;
+            } else {
+                upper = mid;
+            }
+        }
+        Ok(lower)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX_ORE: usize = 1_000_000_000_000;
+
+    #[test]
+    fn test_chemical_amount_parse() -> Result<()> {
+        assert_eq!(
+            ChemicalAmount::parse("12 HKGWZ")?,
+            ChemicalAmount {
+                name: "HKGWZ".to_string(),
+                count: 12,
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_chemical_amount_parse_many() -> Result<()> {
+        assert_eq!(
+            ChemicalAmount::parse_many("3 NPRST, 1 KGSDJ, 1 CTVK")?,
+            vec![
+                ChemicalAmount {
+                    name: "NPRST".to_string(),
+                    count: 3,
+                },
+                ChemicalAmount {
+                    name: "KGSDJ".to_string(),
+                    count: 1,
+                },
+                ChemicalAmount {
+                    name: "CTVK".to_string(),
+                    count: 1,
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_reaction_parse() -> Result<()> {
+        assert_eq!(
+            Reaction::parse("3 NPRST, 1 KGSDJ, 1 CTVK => 2 QMBM")?,
+            Reaction {
+                input: vec![
+                    ChemicalAmount {
+                        name: "NPRST".to_string(),
+                        count: 3,
+                    },
+                    ChemicalAmount {
+                        name: "KGSDJ".to_string(),
+                        count: 1,
+                    },
+                    ChemicalAmount {
+                        name: "CTVK".to_string(),
+                        count: 1,
+                    },
+                ],
+                output: ChemicalAmount {
+                    name: "QMBM".to_string(),
+                    count: 2,
+                },
+            },
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_reactions_parse() -> Result<()> {
+        assert_eq!(
+            Reactions::parse(
+                "
+3 NPRST, 1 KGSDJ, 1 CTVK => 2 QMBM
+7 VJMWM => 4 JHDW
+"
+            )?,
+            Reactions(
+                vec![
+                    (
+                        "QMBM".to_string(),
+                        Reaction {
+                            input: vec![
+                                ChemicalAmount {
+                                    name: "NPRST".to_string(),
+                                    count: 3,
+                                },
+                                ChemicalAmount {
+                                    name: "KGSDJ".to_string(),
+                                    count: 1,
+                                },
+                                ChemicalAmount {
+                                    name: "CTVK".to_string(),
+                                    count: 1,
+                                },
+                            ],
+                            output: ChemicalAmount {
+                                name: "QMBM".to_string(),
+                                count: 2,
+                            },
+                        }
+                    ),
+                    (
+                        "JHDW".to_string(),
+                        Reaction {
+                            input: vec![ChemicalAmount {
+                                name: "VJMWM".to_string(),
+                                count: 7,
+                            },],
+                            output: ChemicalAmount {
+                                name: "JHDW".to_string(),
+                                count: 4,
+                            },
+                        }
+                    ),
+                ]
+                .into_iter()
+                .collect()
+            ),
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_reactions_simple() -> Result<()> {
+        let reactions = Reactions::parse(
+            "
+9 ORE => 2 A
+3 A => 1 FUEL
+        ",
+        )?;
+        assert_eq!(
+            Graph::new(&reactions)?.requirements(ORE, FUEL, 1)?.source,
+            18
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_reactions_composite() -> Result<()> {
+        let reactions = Reactions::parse(
+            "
+9 ORE => 2 A
+8 ORE => 3 B
+3 A, 3 B => 1 FUEL
+        ",
+        )?;
+        assert_eq!(
+            Graph::new(&reactions)?.requirements(ORE, FUEL, 1)?.source,
+            26
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_reactions_leftover() -> Result<()> {
+        let reactions = Reactions::parse(
+            "
+9 ORE => 2 A
+1 A => 3 B
+3 A, 3 B => 1 FUEL
+        ",
+        )?;
+        assert_eq!(
+            Graph::new(&reactions)?.requirements(ORE, FUEL, 1)?.source,
+            18
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_reactions_1() -> Result<()> {
+        let reactions = Reactions::parse(
+            "
+9 ORE => 2 A
+8 ORE => 3 B
+7 ORE => 5 C
+3 A, 4 B => 1 AB
+5 B, 7 C => 1 BC
+4 C, 1 A => 1 CA
+2 AB, 3 BC, 4 CA => 1 FUEL
+        ",
+        )?;
+        assert_eq!(
+            Graph::new(&reactions)?.requirements(ORE, FUEL, 1)?.source,
+            165
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_reactions_2() -> Result<()> {
+        let reactions = Reactions::parse(
+            "
+157 ORE => 5 NZVS
+165 ORE => 6 DCFZ
+44 XJWVT, 5 KHKGT, 1 QDVJ, 29 NZVS, 9 GPVTF, 48 HKGWZ => 1 FUEL
+12 HKGWZ, 1 GPVTF, 8 PSHF => 9 QDVJ
+179 ORE => 7 PSHF
+177 ORE => 5 HKGWZ
+7 DCFZ, 7 PSHF => 2 XJWVT
+165 ORE => 2 GPVTF
+3 DCFZ, 7 NZVS, 5 HKGWZ, 10 PSHF => 8 KHKGT
+        ",
+        )?;
+        assert_eq!(
+            Graph::new(&reactions)?.requirements(ORE, FUEL, 1)?.source,
+            13312
+        );
+        assert_eq!(
+            Graph::new(&reactions)?.max_target(ORE, FUEL, MAX_ORE)?,
+            82892753
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_reactions_3() -> Result<()> {
+        let reactions = Reactions::parse(
+            "
+2 VPVL, 7 FWMGM, 2 CXFTF, 11 MNCFX => 1 STKFG
+17 NVRVD, 3 JNWZP => 8 VPVL
+53 STKFG, 6 MNCFX, 46 VJHF, 81 HVMC, 68 CXFTF, 25 GNMV => 1 FUEL
+22 VJHF, 37 MNCFX => 5 FWMGM
+139 ORE => 4 NVRVD
+144 ORE => 7 JNWZP
+5 MNCFX, 7 RFSQX, 2 FWMGM, 2 VPVL, 19 CXFTF => 3 HVMC
+5 VJHF, 7 MNCFX, 9 VPVL, 37 CXFTF => 6 GNMV
+145 ORE => 6 MNCFX
+1 NVRVD => 8 CXFTF
+1 VJHF, 6 MNCFX => 4 RFSQX
+176 ORE => 6 VJHF
+        ",
+        )?;
+        assert_eq!(
+            Graph::new(&reactions)?.requirements(ORE, FUEL, 1)?.source,
+            180697
+        );
+        assert_eq!(
+            Graph::new(&reactions)?.max_target(ORE, FUEL, MAX_ORE)?,
+            5586022
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_reactions_4() -> Result<()> {
+        let reactions = Reactions::parse(
+            "
+171 ORE => 8 CNZTR
+7 ZLQW, 3 BMBT, 9 XCVML, 26 XMNCP, 1 WPTQ, 2 MZWV, 1 RJRHP => 4 PLWSL
+114 ORE => 4 BHXH
+14 VRPVC => 6 BMBT
+6 BHXH, 18 KTJDG, 12 WPTQ, 7 PLWSL, 31 FHTLT, 37 ZDVW => 1 FUEL
+6 WPTQ, 2 BMBT, 8 ZLQW, 18 KTJDG, 1 XMNCP, 6 MZWV, 1 RJRHP => 6 FHTLT
+15 XDBXC, 2 LTCX, 1 VRPVC => 6 ZLQW
+13 WPTQ, 10 LTCX, 3 RJRHP, 14 XMNCP, 2 MZWV, 1 ZLQW => 1 ZDVW
+5 BMBT => 4 WPTQ
+189 ORE => 9 KTJDG
+1 MZWV, 17 XDBXC, 3 XCVML => 2 XMNCP
+12 VRPVC, 27 CNZTR => 2 XDBXC
+15 KTJDG, 12 BHXH => 5 XCVML
+3 BHXH, 2 VRPVC => 7 MZWV
+121 ORE => 7 VRPVC
+7 XCVML => 6 RJRHP
+5 BHXH, 4 VRPVC => 5 LTCX
+        ",
+        )?;
+        assert_eq!(
+            Graph::new(&reactions)?.requirements(ORE, FUEL, 1)?.source,
+            2210736
+        );
+        assert_eq!(
+            Graph::new(&reactions)?.max_target(ORE, FUEL, MAX_ORE)?,
+            460664
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_graph_max_target() -> Result<()> {
+        let graph = Graph::new(&Reactions::parse(
+            "
+9 ORE => 2 A
+8 ORE => 3 B
+7 ORE => 5 C
+3 A, 4 B => 1 AB
+5 B, 7 C => 1 BC
+4 C, 1 A => 1 CA
+2 AB, 3 BC, 4 CA => 1 FUEL
+        ",
+        )?)?;
+        assert_eq!(graph.max_target(ORE, FUEL, 164)?, 0);
+        assert_eq!(graph.max_target(ORE, FUEL, 165)?, 1);
+        assert_eq!(graph.max_target(ORE, FUEL, 1584)?, 10);
+        assert_eq!(graph.max_target(ORE, "B", 16)?, 6);
+        assert_eq!(
+            graph.max_target("A", "B", 100),
+            Err(GraphError::MissingRecipe(ORE.to_string()))
+        );
+        let graph = Graph::new(&Reactions::parse(
+            "9 ORE => 2 A\n3 A => 2 B\n5 B => 1 FUEL",
+        )?)?;
+        assert_eq!(graph.max_target("A", FUEL, 100)?, 13);
+        Ok(())
+    }
+
+    #[test]
+    fn test_graph_requirements() -> Result<()> {
+        let graph = Graph::new(&Reactions::parse(
+            "
+9 ORE => 2 A
+8 ORE => 3 B
+7 ORE => 5 C
+3 A, 4 B => 1 AB
+5 B, 7 C => 1 BC
+4 C, 1 A => 1 CA
+2 AB, 3 BC, 4 CA => 1 FUEL
+        ",
+        )?)?;
+        let requirements = graph.requirements(ORE, FUEL, 1)?;
+        assert_eq!(requirements.source, 165);
+        assert_eq!(
+            requirements.leftovers,
+            vec![("B".to_string(), 1), ("C".to_string(), 3)]
+                .into_iter()
+                .collect()
+        );
+        assert_eq!(graph.requirements(ORE, FUEL, 10)?.source, 1584);
+        // Other sources and targets
+        assert_eq!(graph.requirements(ORE, "B", 4)?.source, 16);
+        let graph = Graph::new(&Reactions::parse(
+            "9 ORE => 2 A\n3 A => 2 B\n5 B => 1 FUEL",
+        )?)?;
+        assert_eq!(graph.requirements("A", FUEL, 1)?.source, 9);
+        assert_eq!(graph.requirements("B", FUEL, 2)?.source, 10);
+        Ok(())
+    }
+
+    #[test]
+    fn test_graph_errors() -> Result<()> {
+        let graph = Graph::new(&Reactions::parse("9 ORE => 2 A\n3 A, 1 B => 1 FUEL")?)?;
+        assert_eq!(
+            graph.requirements(ORE, FUEL, 1),
+            Err(GraphError::MissingRecipe("B".to_string()))
+        );
+        assert_eq!(
+            graph.requirements(ORE, "C", 1),
+            Err(GraphError::UnknownChemical("C".to_string()))
+        );
+
+        let err = Graph::new(&Reactions::parse(
+            "1 ORE, 1 C => 1 A\n1 A => 1 B\n1 B => 1 C\n1 C => 1 FUEL",
+        )?)
+        .unwrap_err();
+        assert_eq!(
+            err,
+            GraphError::Cycle(vec![
+                "A".to_string(),
+                "C".to_string(),
+                "B".to_string(),
+                "A".to_string()
+            ])
+        );
+        assert_eq!(err.to_string(), "reactions form a cycle: A => C => B => A");
+
+        let err = Reactions::parse("1 ORE => 1 A\n2 ORE => 3 A\n1 A => 1 FUEL").unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<GraphError>(),
+            Some(&GraphError::DuplicateRecipe("A".to_string()))
+        );
+        assert_eq!(
+            Graph::new(&Reactions::parse("1 ORE => 0 A\n1 A => 1 FUEL")?),
+            Err(GraphError::ZeroOutput("A".to_string()))
+        );
+
+        // Free chemicals can be made without limit
+        let graph = Graph::new(&Reactions::parse("0 ORE => 1 A\n1 A => 1 FUEL")?)?;
+        assert_eq!(graph.requirements(ORE, FUEL, 5)?.source, 0);
+        assert_eq!(
+            graph.max_target(ORE, FUEL, 100),
+            Err(GraphError::Unlimited {
+                source: ORE.to_string(),
+                target: FUEL.to_string()
+            })
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,207 @@
+use anyhow::{anyhow, Result};
+
+use helper::grid::Grid;
+use helper::image::{Image, Rgb};
+
+pub const BLACK: u8 = 0;
+pub const WHITE: u8 = 1;
+pub const TRANSPARENT: u8 = 2;
+
+/// An image in the Space Image Format, a stack of layers of digits where the
+/// first layer is at the front.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sif {
+    width: usize,
+    height: usize,
+    layers: Vec<Vec<u8>>,
+}
+
+impl Sif {
+    /// Builds an image from layers, which must all be `width * height`.
+    pub fn from_layers(width: usize, height: usize, layers: Vec<Vec<u8>>) -> Result<Self> {
+        if width == 0 || height == 0 {
+            return Err(anyhow!(
+                "image must be at least 1x1, got {}x{}",
+                width,
+                height
+            ));
+        }
+        if let Some(index) = layers.iter().position(|l| l.len() != width * height) {
+            return Err(anyhow!(
+                "layer {} has {} pixels, expected {}",
+                index,
+                layers[index].len(),
+                width * height
+            ));
+        }
+        if let Some(d) = layers.iter().flatten().find(|&&d| d > 9) {
+            return Err(anyhow!("pixel {} is not a digit", d));
+        }
+        Ok(Self {
+            width,
+            height,
+            layers,
+        })
+    }
+
+    /// Decodes a string of digits, which must be a whole number of layers.
+    pub fn decode(data: &str, width: usize, height: usize) -> Result<Self> {
+        let data = data.trim();
+        let layer_size = width * height;
+        if layer_size == 0 || !data.len().is_multiple_of(layer_size) {
+            return Err(anyhow!(
+                "{} digits is not a whole number of {}x{} layers",
+                data.len(),
+                width,
+                height
+            ));
+        }
+        let digits = data
+            .chars()
+            .enumerate()
+            .map(|(index, c)| {
+                c.to_digit(10)
+                    .map(|d| d as u8)
+                    .ok_or_else(|| anyhow!("'{}' at {} is not a digit", c, index))
+            })
+            .collect::<Result<Vec<u8>>>()?;
+        Self::from_layers(
+            width,
+            height,
+            digits.chunks(layer_size).map(|l| l.to_vec()).collect(),
+        )
+    }
+
+    pub fn encode(&self) -> String {
+        self.layers
+            .iter()
+            .flatten()
+            .map(|d| char::from(b'0' + d))
+            .collect()
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn layers(&self) -> &[Vec<u8>] {
+        &self.layers
+    }
+
+    /// How many of each digit are in a layer.
+    pub fn histogram(&self, layer: usize) -> Option<[usize; 10]> {
+        self.layers.get(layer).map(|l| {
+            let mut counts = [0; 10];
+            for &d in l {
+                counts[d as usize] += 1;
+            }
+            counts
+        })
+    }
+
+    pub fn histograms(&self) -> Vec<[usize; 10]> {
+        (0..self.layers.len())
+            .filter_map(|l| self.histogram(l))
+            .collect()
+    }
+
+    /// The number of ones times the number of twos in the layer with the
+    /// fewest zeroes, used to check an image isn't corrupt.
+    pub fn checksum(&self) -> Option<usize> {
+        self.histograms()
+            .into_iter()
+            .min_by_key(|h| h[0])
+            .map(|h| h[1] * h[2])
+    }
+
+    /// Flattens the layers, each pixel being the frontmost which isn't
+    /// transparent, or transparent if they all are.
+    pub fn compose(&self) -> Grid<u8> {
+        let mut pixels = vec![TRANSPARENT; self.width * self.height];
+        for layer in self.layers.iter().rev() {
+            for (pixel, &d) in pixels.iter_mut().zip(layer) {
+                if d != TRANSPARENT {
+                    *pixel = d;
+                }
+            }
+        }
+        Grid::from_rows(pixels.chunks(self.width).map(|r| r.to_vec()).collect())
+            .expect("rows are all the image width")
+    }
+
+    /// Draws the composed image with white as `#`, suitable for
+    /// `ocr::recognise`.
+    pub fn render(&self) -> String {
+        self.compose()
+            .rows()
+            .map(|row| {
+                row.iter()
+                    .map(|&d| if d == WHITE { '#' } else { ' ' })
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    pub fn to_image(&self, scale: usize) -> Image {
+        Image::from_grid(
+            &self.compose(),
+            |&d| match d {
+                BLACK => Rgb::BLACK,
+                WHITE => Rgb::WHITE,
+                _ => Rgb::GREY,
+            },
+            scale,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() -> Result<()> {
+        let sif = Sif::decode("123456789012\n", 3, 2)?;
+        assert_eq!(
+            sif.layers(),
+            &[vec![1, 2, 3, 4, 5, 6], vec![7, 8, 9, 0, 1, 2]]
+        );
+        assert_eq!(sif.encode(), "123456789012");
+        assert_eq!(Sif::decode(&sif.encode(), 3, 2)?, sif);
+
+        assert!(Sif::decode("12345678901", 3, 2).is_err());
+        assert!(Sif::decode("12345a789012", 3, 2).is_err());
+        assert!(Sif::decode("", 0, 2).is_err());
+        assert!(Sif::from_layers(2, 1, vec![vec![1, 2], vec![1]]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_histograms() -> Result<()> {
+        let sif = Sif::decode("001222112000", 3, 2)?;
+        assert_eq!(sif.histogram(0), Some([2, 1, 3, 0, 0, 0, 0, 0, 0, 0]));
+        assert_eq!(sif.histogram(1), Some([3, 2, 1, 0, 0, 0, 0, 0, 0, 0]));
+        assert_eq!(sif.histogram(2), None);
+        assert_eq!(sif.checksum(), Some(3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_compose() -> Result<()> {
+        let sif = Sif::decode("0222112222120000", 2, 2)?;
+        assert_eq!(
+            sif.compose().rows().collect::<Vec<&[u8]>>(),
+            vec![&[0, 1], &[1, 0]]
+        );
+        assert_eq!(sif.render(), " #\n# ");
+
+        let sif = Sif::decode("2222", 2, 1)?;
+        assert_eq!(sif.compose().rows().collect::<Vec<&[u8]>>(), vec![&[2, 2]]);
+        Ok(())
+    }
+}
@@ -0,0 +1,580 @@
+use anyhow::{anyhow, Result};
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+
+use helper::dir::{Dir, YAxis};
+use helper::point::IPoint2;
+use helper::scan;
+
+/// A wire laid out as moves from the origin.
+pub type Wire = Vec<WirePath>;
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct WirePath {
+    pub dir: Dir,
+    pub dist: isize,
+}
+
+/// Parses a move such as `U5` or `R999`.
+pub fn parse_wire_path(input: &str) -> Result<WirePath> {
+    if input.len() < 2 {
+        return Err(anyhow!("Expected minimum length 2, got: {}", input.len()));
+    }
+    let mut chars = input.chars();
+    let dir = match chars.next() {
+        Some(c @ ('U' | 'D' | 'L' | 'R')) => Dir::parse(c)?,
+        _ => return Err(anyhow!("expected U, D, L or R: {}", input)),
+    };
+    Ok(WirePath {
+        dir,
+        dist: chars.collect::<String>().parse()?,
+    })
+}
+
+/// Parses a comma separated list of moves.
+pub fn parse_wire(input: &str) -> Result<Wire> {
+    scan::comma_list(input, parse_wire_path)
+}
+
+const ORIGIN: IPoint2 = IPoint2 { x: 0, y: 0 };
+
+/// A straight run of a wire, with the steps taken along the wire to reach
+/// its start. `position` is where it is in the list of segments, so the
+/// segments either side of a corner always have consecutive positions.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+struct Segment {
+    wire: usize,
+    position: usize,
+    start: IPoint2,
+    end: IPoint2,
+    steps: usize,
+}
+
+impl Segment {
+    fn is_horizontal(&self) -> bool {
+        self.start.y == self.end.y
+    }
+
+    fn x_range(&self) -> (isize, isize) {
+        (self.start.x.min(self.end.x), self.start.x.max(self.end.x))
+    }
+
+    fn y_range(&self) -> (isize, isize) {
+        (self.start.y.min(self.end.y), self.start.y.max(self.end.y))
+    }
+
+    fn steps_to(&self, p: IPoint2) -> usize {
+        self.steps + (p - self.start).manhattan() as usize
+    }
+
+    // Consecutive segments of a wire always meet at the corner between them
+    fn is_corner(&self, other: &Segment, p: IPoint2) -> bool {
+        self.wire == other.wire
+            && ((self.position + 1 == other.position && p == self.end)
+                || (other.position + 1 == self.position && p == other.end))
+    }
+}
+
+/// The segments of the chosen wires, skipping moves of zero length.
+fn segments(wires: &[Wire], chosen: &[usize]) -> Vec<Segment> {
+    let mut segments = vec![];
+    for &wire in chosen {
+        let mut start = ORIGIN;
+        let mut steps = 0;
+        for path in &wires[wire] {
+            let end = start + path.dir.unit(YAxis::Down) * path.dist;
+            if path.dist > 0 {
+                segments.push(Segment {
+                    wire,
+                    position: segments.len(),
+                    start,
+                    end,
+                    steps,
+                });
+            }
+            start = end;
+            steps += path.dist as usize;
+        }
+    }
+    segments
+}
+
+/// Where two wires cross, or a wire crosses itself if both wires are the
+/// same. Wires running along each other cross at every point from `start` to
+/// `end`, otherwise the two are the same point. The combined steps along the
+/// wires change evenly between the ends. A point can be in more than one
+/// crossing if the wires pass it more than once.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Crossing {
+    pub wires: [usize; 2],
+    pub start: IPoint2,
+    pub end: IPoint2,
+    pub start_steps: usize,
+    pub end_steps: usize,
+}
+
+impl Crossing {
+    fn new(a: &Segment, b: &Segment, start: IPoint2, end: IPoint2) -> Self {
+        let (a, b) = if a.position <= b.position {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        Self {
+            wires: [a.wire, b.wire],
+            start,
+            end,
+            start_steps: a.steps_to(start) + b.steps_to(start),
+            end_steps: a.steps_to(end) + b.steps_to(end),
+        }
+    }
+
+    /// Every point in the crossing, from `start` to `end`.
+    pub fn points(&self) -> impl Iterator<Item = IPoint2> {
+        let (start, step) = (self.start, (self.end - self.start).signum());
+        let len = (self.end - self.start).manhattan();
+        (0..=len).map(move |n| start + step * n)
+    }
+
+    /// The combined steps at a point in the crossing.
+    pub fn steps_at(&self, p: IPoint2) -> Option<usize> {
+        let len = (self.end - self.start).manhattan();
+        let along = (p - self.start).manhattan();
+        let on_line = along == 0 || (p - self.start).signum() == (self.end - self.start).signum();
+        if along > len || !on_line {
+            return None;
+        }
+        if len == 0 {
+            return Some(self.start_steps);
+        }
+        let diff = self.end_steps as isize - self.start_steps as isize;
+        Some((self.start_steps as isize + diff * along / len) as usize)
+    }
+
+    /// The fewest combined steps to any point in the crossing.
+    pub fn steps(&self) -> usize {
+        self.start_steps.min(self.end_steps)
+    }
+
+    /// The distance to the origin from the closest point in the crossing.
+    pub fn manhattan(&self) -> isize {
+        let clamp = |v: isize, a: isize, b: isize| v.clamp(a.min(b), a.max(b));
+        IPoint2 {
+            x: clamp(0, self.start.x, self.end.x),
+            y: clamp(0, self.start.y, self.end.y),
+        }
+        .manhattan()
+    }
+}
+
+/// Finds every crossing between the segments, including where wires cross
+/// themselves, but not at the origin. Horizontal and vertical segments are
+/// crossed with a sweep line along x, and segments lying along the same line
+/// are checked for overlaps.
+fn crossings_of(segments: &[Segment]) -> Vec<Crossing> {
+    let mut found: Vec<Crossing> = vec![];
+
+    // Horizontal segments are active between their ends, and each vertical
+    // segment crosses the active ones within its range
+    #[derive(PartialEq, Eq, PartialOrd, Ord)]
+    enum Event {
+        Start,
+        Vertical,
+        End,
+    }
+    let mut events: Vec<(isize, Event, usize)> = vec![];
+    for (i, s) in segments.iter().enumerate() {
+        let (x1, x2) = s.x_range();
+        if s.is_horizontal() {
+            events.push((x1, Event::Start, i));
+            events.push((x2, Event::End, i));
+        } else {
+            events.push((x1, Event::Vertical, i));
+        }
+    }
+    events.sort();
+    let mut active: BTreeMap<isize, Vec<usize>> = BTreeMap::new();
+    for (x, event, i) in events {
+        let s = &segments[i];
+        match event {
+            Event::Start => active.entry(s.start.y).or_default().push(i),
+            Event::End => {
+                if let Some(at_y) = active.get_mut(&s.start.y) {
+                    at_y.retain(|&a| a != i);
+                }
+            }
+            Event::Vertical => {
+                let (y1, y2) = s.y_range();
+                for (&y, at_y) in active.range(y1..=y2) {
+                    for &h in at_y {
+                        let point = IPoint2 { x, y };
+                        if point != ORIGIN && !segments[h].is_corner(s, point) {
+                            found.push(Crossing::new(&segments[h], s, point, point));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Segments along the same line cross along the whole of their overlap,
+    // less the origin and any corner, which can only be at either end
+    let mut lines: HashMap<(bool, isize), Vec<usize>> = HashMap::new();
+    for (i, s) in segments.iter().enumerate() {
+        let line = if s.is_horizontal() {
+            s.start.y
+        } else {
+            s.start.x
+        };
+        lines.entry((s.is_horizontal(), line)).or_default().push(i);
+    }
+    for ((horizontal, line), mut on_line) in lines {
+        let range = |s: &Segment| {
+            if horizontal {
+                s.x_range()
+            } else {
+                s.y_range()
+            }
+        };
+        let at = |along: isize| {
+            if horizontal {
+                IPoint2 { x: along, y: line }
+            } else {
+                IPoint2 { x: line, y: along }
+            }
+        };
+        on_line.sort_by_key(|&i| range(&segments[i]));
+        for (n, &a) in on_line.iter().enumerate() {
+            let (a, (_, a_max)) = (&segments[a], range(&segments[a]));
+            for &b in &on_line[n + 1..] {
+                let (b, (b_min, b_max)) = (&segments[b], range(&segments[b]));
+                if b_min > a_max {
+                    break;
+                }
+                let (mut from, mut to) = (b_min, a_max.min(b_max));
+                if a.is_corner(b, at(from)) {
+                    from += 1;
+                }
+                if a.is_corner(b, at(to)) {
+                    to -= 1;
+                }
+                let parts = if line == 0 && (from..=to).contains(&0) {
+                    vec![(from, -1), (1, to)]
+                } else {
+                    vec![(from, to)]
+                };
+                for (from, to) in parts {
+                    if from <= to {
+                        found.push(Crossing::new(a, b, at(from), at(to)));
+                    }
+                }
+            }
+        }
+    }
+
+    // Only keep the fewest steps for crossings at the same single point
+    found.sort_by_key(|c| (c.wires, c.start, c.end, c.start_steps, c.end_steps));
+    found.dedup_by(|b, a| {
+        a.start == a.end && (a.wires, a.start, a.end) == (b.wires, b.start, b.end)
+    });
+    // and drop single points already part of an overlap with as few steps
+    let overlaps: Vec<Crossing> = found.iter().filter(|c| c.start != c.end).copied().collect();
+    found.retain(|c| {
+        c.start != c.end
+            || !overlaps.iter().any(|o| {
+                o.wires == c.wires && o.steps_at(c.start).is_some_and(|s| s <= c.start_steps)
+            })
+    });
+    found.sort_by_key(|c| (c.steps(), c.start, c.end, c.wires));
+    found
+}
+
+/// Finds every crossing between the wires, including where wires cross
+/// themselves.
+pub fn crossings(wires: &[Wire]) -> Vec<Crossing> {
+    crossings_of(&segments(wires, &(0..wires.len()).collect::<Vec<usize>>()))
+}
+
+/// Crossings between two wires, which can be the same wire to find where it
+/// crosses itself. Only the segments of those wires are looked at.
+pub fn crossings_between(wires: &[Wire], a: usize, b: usize) -> Vec<Crossing> {
+    let pair = [a.min(b), a.max(b)];
+    let chosen = if a == b { vec![a] } else { pair.to_vec() };
+    crossings_of(&segments(wires, &chosen))
+        .into_iter()
+        .filter(|c| c.wires == pair)
+        .collect()
+}
+
+/// Crossings between different wires.
+pub fn intersections(wires: &[Wire]) -> Vec<Crossing> {
+    crossings(wires)
+        .into_iter()
+        .filter(|c| c.wires[0] != c.wires[1])
+        .collect()
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Metric {
+    // Distance from the origin
+    Manhattan,
+    // Combined steps along the wires
+    Steps,
+}
+
+impl Metric {
+    pub fn of(self, crossing: &Crossing) -> usize {
+        match self {
+            Metric::Manhattan => crossing.manhattan() as usize,
+            Metric::Steps => crossing.steps(),
+        }
+    }
+}
+
+pub fn closest(crossings: &[Crossing], metric: Metric) -> Option<usize> {
+    crossings.iter().map(|c| metric.of(c)).min()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashSet;
+
+    // Walks every cell of the wires to find where they cross, to check the
+    // segment engine against
+    fn walk(wires: &[Wire]) -> HashMap<(IPoint2, [usize; 2]), usize> {
+        let mut visits: HashMap<IPoint2, Vec<(usize, usize)>> = HashMap::new();
+        for (wire, paths) in wires.iter().enumerate() {
+            let (mut at, mut steps) = (ORIGIN, 0);
+            for path in paths {
+                for _ in 0..path.dist {
+                    at += path.dir.unit(YAxis::Down);
+                    steps += 1;
+                    visits.entry(at).or_default().push((wire, steps));
+                }
+            }
+        }
+        let mut found = HashMap::new();
+        for (point, v) in visits {
+            for (n, &(a, a_steps)) in v.iter().enumerate() {
+                for &(b, b_steps) in &v[n + 1..] {
+                    let best = found
+                        .entry((point, [a.min(b), a.max(b)]))
+                        .or_insert(usize::MAX);
+                    *best = (*best).min(a_steps + b_steps);
+                }
+            }
+        }
+        found
+    }
+
+    fn points(crossings: &[Crossing]) -> HashSet<IPoint2> {
+        crossings.iter().flat_map(|c| c.points()).collect()
+    }
+
+    #[test]
+    fn test_parse_wire_path() {
+        assert_eq!(
+            parse_wire_path("U5").unwrap(),
+            WirePath {
+                dir: Dir::North,
+                dist: 5,
+            },
+        );
+        assert_eq!(
+            parse_wire_path("D4").unwrap(),
+            WirePath {
+                dir: Dir::South,
+                dist: 4,
+            },
+        );
+        assert_eq!(
+            parse_wire_path("L35").unwrap(),
+            WirePath {
+                dir: Dir::West,
+                dist: 35,
+            },
+        );
+        assert_eq!(
+            parse_wire_path("R999").unwrap(),
+            WirePath {
+                dir: Dir::East,
+                dist: 999,
+            },
+        );
+        assert!(parse_wire_path("X1").is_err());
+        assert!(parse_wire_path("U").is_err());
+    }
+
+    #[test]
+    fn test_parse_wire() {
+        assert_eq!(
+            parse_wire("R8,U5,L5,D3").unwrap(),
+            vec![
+                WirePath {
+                    dir: Dir::East,
+                    dist: 8,
+                },
+                WirePath {
+                    dir: Dir::North,
+                    dist: 5,
+                },
+                WirePath {
+                    dir: Dir::West,
+                    dist: 5,
+                },
+                WirePath {
+                    dir: Dir::South,
+                    dist: 3,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn test_intersections() {
+        let point = |x, y, steps| Crossing {
+            wires: [0, 1],
+            start: IPoint2 { x, y },
+            end: IPoint2 { x, y },
+            start_steps: steps,
+            end_steps: steps,
+        };
+        assert_eq!(
+            intersections(&[
+                parse_wire("R8,U5,L5,D3").unwrap(),
+                parse_wire("U7,R6,D4,L4").unwrap(),
+            ]),
+            vec![point(6, -5, 30), point(3, -3, 40)],
+        );
+    }
+
+    #[test]
+    fn test_closest() {
+        let examples = [
+            ("R8,U5,L5,D3", "U7,R6,D4,L4", 6, 30),
+            (
+                "R75,D30,R83,U83,L12,D49,R71,U7,L72",
+                "U62,R66,U55,R34,D71,R55,D58,R83",
+                159,
+                610,
+            ),
+            (
+                "R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51",
+                "U98,R91,D20,R16,D67,R40,U7,R15,U6,R7",
+                135,
+                410,
+            ),
+        ];
+        for (a, b, manhattan, steps) in examples.iter() {
+            let found = intersections(&[parse_wire(a).unwrap(), parse_wire(b).unwrap()]);
+            assert_eq!(closest(&found, Metric::Manhattan), Some(*manhattan));
+            assert_eq!(closest(&found, Metric::Steps), Some(*steps));
+        }
+    }
+
+    #[test]
+    fn test_crossings() {
+        // The first wire crosses itself at 3,-1, and runs along the second
+        // from 4,-1 to 7,-1
+        let wires = vec![
+            parse_wire("R3,U2,L1,D1,R7").unwrap(),
+            parse_wire("U1,R1,D2,R3,U2,R3,D2").unwrap(),
+            parse_wire("D5").unwrap(),
+        ];
+        assert_eq!(
+            crossings_between(&wires, 0, 0),
+            vec![Crossing {
+                wires: [0, 0],
+                start: IPoint2 { x: 3, y: -1 },
+                end: IPoint2 { x: 3, y: -1 },
+                start_steps: 12,
+                end_steps: 12,
+            }]
+        );
+        assert_eq!(
+            crossings_between(&wires, 1, 0)
+                .into_iter()
+                .map(|c| (c.start, c.end))
+                .collect::<Vec<(IPoint2, IPoint2)>>(),
+            vec![
+                (IPoint2 { x: 1, y: 0 }, IPoint2 { x: 1, y: 0 }),
+                (IPoint2 { x: 4, y: -1 }, IPoint2 { x: 7, y: -1 }),
+            ]
+        );
+        assert_eq!(crossings_between(&wires, 0, 2), vec![]);
+        assert_eq!(crossings_between(&wires, 1, 2), vec![]);
+        assert_eq!(crossings_between(&wires, 2, 2), vec![]);
+        assert_eq!(closest(&intersections(&wires), Metric::Manhattan), Some(1));
+        assert_eq!(closest(&intersections(&wires), Metric::Steps), Some(4));
+    }
+
+    #[test]
+    fn test_zero_length_step() {
+        // The corner at 3,0 is still a corner with the empty move between
+        let wires = vec![parse_wire("R3,U0,D2").unwrap()];
+        assert_eq!(crossings_between(&wires, 0, 0), vec![]);
+        let wires = vec![parse_wire("R3,L0,L2,U0,R0,R1").unwrap()];
+        assert_eq!(
+            points(&crossings_between(&wires, 0, 0)),
+            [IPoint2 { x: 1, y: 0 }, IPoint2 { x: 2, y: 0 }]
+                .iter()
+                .copied()
+                .collect()
+        );
+    }
+
+    #[test]
+    fn test_long_overlap() {
+        let wires = vec![
+            parse_wire("R1000000000").unwrap(),
+            parse_wire("L5,R2000000000").unwrap(),
+        ];
+        let found = intersections(&wires);
+        assert_eq!(
+            found,
+            vec![Crossing {
+                wires: [0, 1],
+                start: IPoint2 { x: 1, y: 0 },
+                end: IPoint2 {
+                    x: 1_000_000_000,
+                    y: 0
+                },
+                start_steps: 12,
+                end_steps: 2_000_000_010,
+            }]
+        );
+        assert_eq!(closest(&found, Metric::Manhattan), Some(1));
+        assert_eq!(closest(&found, Metric::Steps), Some(12));
+    }
+
+    #[test]
+    fn test_crossings_match_walk() {
+        let wires = vec![
+            parse_wire("R8,U5,L5,D3,R0,D4,L9,U3,R12,L6").unwrap(),
+            parse_wire("U7,R6,D4,L4,L3,D6,R9,U2,D8").unwrap(),
+            parse_wire("D2,L3,R6,U4,D1,R2,L7,U6").unwrap(),
+        ];
+        let walked = walk(&wires);
+        let found = crossings(&wires);
+        let mut expected: Vec<(IPoint2, [usize; 2])> = walked.keys().copied().collect();
+        let mut actual: Vec<(IPoint2, [usize; 2])> = found
+            .iter()
+            .flat_map(|c| c.points().map(move |p| (p, c.wires)))
+            .collect();
+        expected.sort();
+        actual.sort();
+        actual.dedup();
+        assert_eq!(actual, expected);
+        for ((point, pair), steps) in walked {
+            let best = found
+                .iter()
+                .filter(|c| c.wires == pair)
+                .filter_map(|c| c.steps_at(point))
+                .min();
+            assert_eq!(best, Some(steps), "{:?} {:?}", point, pair);
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use std::convert::TryFrom;
+
+/// Where a sequence of states starts repeating. The states before `start` are
+/// never seen again, and from `start` on the state at step `n` is the same as
+/// at step `n + len`.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub struct Cycle {
+    pub start: usize,
+    pub len: usize,
+}
+
+impl Cycle {
+    /// The first step whose state was seen at an earlier step.
+    pub fn first_repeat(&self) -> usize {
+        self.start + self.len
+    }
+
+    /// The earliest step with the same state as step `n`, which makes it cheap
+    /// to find the state after a huge number of steps.
+    pub fn reduce(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.len
+        }
+    }
+
+    /// Combines the cycles of independent parts of a state into the cycle of
+    /// the whole. The whole only repeats once every part is in its loop, and
+    /// then with a period that's a multiple of every part's period.
+    pub fn combine<I: IntoIterator<Item = Cycle>>(cycles: I) -> Option<Cycle> {
+        let mut start = 0;
+        let mut congruence = (0, 1);
+        for c in cycles {
+            start = start.max(c.start);
+            congruence = crt(congruence, (0, c.len as i128))?;
+        }
+        Some(Cycle {
+            start,
+            len: usize::try_from(congruence.1).ok()?,
+        })
+    }
+}
+
+/// Finds the cycle in the sequence `initial`, `step(initial)`, ... using
+/// Brent's algorithm, which only keeps two states at a time.
+pub fn brent<T, F>(initial: T, step: F) -> Cycle
+where
+    T: Clone + PartialEq,
+    F: Fn(&T) -> T,
+{
+    // Find the length by moving the tortoise to the hare at powers of two
+    let mut power = 1;
+    let mut len = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+    while tortoise != hare {
+        if power == len {
+            tortoise = hare.clone();
+            power *= 2;
+            len = 0;
+        }
+        hare = step(&hare);
+        len += 1;
+    }
+
+    // Then find the start with the hare a length ahead of the tortoise
+    let mut tortoise = initial.clone();
+    let mut hare = initial;
+    for _ in 0..len {
+        hare = step(&hare);
+    }
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+    Cycle { start, len }
+}
+
+/// Returns `(gcd(a, b), x, y)` where `a * x + b * y = gcd(a, b)`.
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a.rem_euclid(b));
+        (g, y, x - a.div_euclid(b) * y)
+    }
+}
+
+/// Solves `n ≡ a.0 (mod a.1)` and `n ≡ b.0 (mod b.1)` with the Chinese
+/// remainder theorem, returning `(n, lcm)` with the smallest non-negative
+/// `n`. The moduli don't need to be coprime, but there's no solution if they
+/// disagree on their common factor.
+pub fn crt(a: (i128, i128), b: (i128, i128)) -> Option<(i128, i128)> {
+    let (g, x, _) = extended_gcd(a.1, b.1);
+    let diff = b.0 - a.0;
+    if diff % g != 0 {
+        return None;
+    }
+    let lcm = a.1 / g * b.1;
+    let n = a.0 + a.1 * (diff / g * x).rem_euclid(b.1 / g);
+    Some((n.rem_euclid(lcm), lcm))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_brent() {
+        // 0, 1, 2, 3, 4, 5, 2, 3, 4, 5, ...
+        let cycle = brent(0, |&n| if n == 5 { 2 } else { n + 1 });
+        assert_eq!(cycle, Cycle { start: 2, len: 4 });
+        assert_eq!(cycle.first_repeat(), 6);
+        assert_eq!(cycle.reduce(1), 1);
+        assert_eq!(cycle.reduce(1_000_001), 5);
+
+        assert_eq!(brent(7, |&n| n), Cycle { start: 0, len: 1 });
+        assert_eq!(brent(0, |&n| (n + 1) % 10), Cycle { start: 0, len: 10 });
+        assert_eq!(brent(3, |&n| (n * n + 1) % 255), Cycle { start: 2, len: 6 });
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt((2, 3), (3, 5)), Some((8, 15)));
+        assert_eq!(crt((8, 15), (2, 7)), Some((23, 105)));
+        assert_eq!(crt((1, 4), (3, 6)), Some((9, 12)));
+        assert_eq!(crt((1, 4), (2, 6)), None);
+        assert_eq!(crt((0, 1), (4, 9)), Some((4, 9)));
+    }
+
+    #[test]
+    fn test_combine() {
+        let parts = vec![
+            Cycle { start: 0, len: 4 },
+            Cycle { start: 3, len: 6 },
+            Cycle { start: 1, len: 5 },
+        ];
+        assert_eq!(Cycle::combine(parts), Some(Cycle { start: 3, len: 60 }));
+        assert_eq!(Cycle::combine(vec![]), Some(Cycle { start: 0, len: 1 }));
+
+        // The whole of a pair of sequences matches combining each
+        let step = |&(a, b): &(usize, usize)| {
+            (
+                if a == 5 { 2 } else { a + 1 },
+                if b == 9 { 5 } else { b + 1 },
+            )
+        };
+        let whole = brent((0, 0), step);
+        let a = brent(0, |&a| step(&(a, 0)).0);
+        let b = brent(0, |&b| step(&(0, b)).1);
+        assert_eq!(Cycle::combine(vec![a, b]), Some(whole));
+    }
+}
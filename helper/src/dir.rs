@@ -0,0 +1,316 @@
+use anyhow::{anyhow, Result};
+
+use std::fmt;
+
+use crate::point::IPoint2;
+
+/// Which way the y axis points, puzzle input read as text usually has y
+/// pointing down.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash, Default)]
+pub enum YAxis {
+    #[default]
+    Down,
+    Up,
+}
+
+impl YAxis {
+    fn north(self) -> isize {
+        match self {
+            YAxis::Down => -1,
+            YAxis::Up => 1,
+        }
+    }
+}
+
+/// One of the four orthogonal directions.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub enum Dir {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Dir {
+    /// All directions, clockwise from north.
+    pub fn variants() -> Vec<Self> {
+        vec![Dir::North, Dir::East, Dir::South, Dir::West]
+    }
+
+    /// Parses `UDLR`, `NSEW` or `^v<>`.
+    pub fn parse(input: char) -> Result<Self> {
+        match input {
+            'U' | 'N' | '^' => Ok(Dir::North),
+            'R' | 'E' | '>' => Ok(Dir::East),
+            'D' | 'S' | 'v' => Ok(Dir::South),
+            'L' | 'W' | '<' => Ok(Dir::West),
+            _ => Err(anyhow!("invalid dir: {}", input)),
+        }
+    }
+
+    pub fn turn_left(self) -> Self {
+        match self {
+            Dir::North => Dir::West,
+            Dir::East => Dir::North,
+            Dir::South => Dir::East,
+            Dir::West => Dir::South,
+        }
+    }
+
+    pub fn turn_right(self) -> Self {
+        match self {
+            Dir::North => Dir::East,
+            Dir::East => Dir::South,
+            Dir::South => Dir::West,
+            Dir::West => Dir::North,
+        }
+    }
+
+    pub fn turn_around(self) -> Self {
+        match self {
+            Dir::North => Dir::South,
+            Dir::East => Dir::West,
+            Dir::South => Dir::North,
+            Dir::West => Dir::East,
+        }
+    }
+
+    pub fn unit(self, y_axis: YAxis) -> IPoint2 {
+        match self {
+            Dir::North => IPoint2 {
+                x: 0,
+                y: y_axis.north(),
+            },
+            Dir::East => IPoint2 { x: 1, y: 0 },
+            Dir::South => IPoint2 {
+                x: 0,
+                y: -y_axis.north(),
+            },
+            Dir::West => IPoint2 { x: -1, y: 0 },
+        }
+    }
+
+    /// The direction of a unit vector, or `None` if it isn't one.
+    pub fn from_unit(p: IPoint2, y_axis: YAxis) -> Option<Self> {
+        Self::variants()
+            .into_iter()
+            .find(|dir| dir.unit(y_axis) == p)
+    }
+
+    /// The arrow pointing this direction, with north drawn up.
+    pub fn arrow(self) -> char {
+        match self {
+            Dir::North => '^',
+            Dir::East => '>',
+            Dir::South => 'v',
+            Dir::West => '<',
+        }
+    }
+}
+
+impl fmt::Display for Dir {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.arrow())
+    }
+}
+
+/// One of the four orthogonal or four diagonal directions.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub enum Dir8 {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Dir8 {
+    /// All directions, clockwise from north.
+    pub fn variants() -> Vec<Self> {
+        vec![
+            Dir8::North,
+            Dir8::NorthEast,
+            Dir8::East,
+            Dir8::SouthEast,
+            Dir8::South,
+            Dir8::SouthWest,
+            Dir8::West,
+            Dir8::NorthWest,
+        ]
+    }
+
+    fn index(self) -> usize {
+        Self::variants().iter().position(|d| *d == self).unwrap()
+    }
+
+    fn rotate(self, eighths: usize) -> Self {
+        Self::variants()[(self.index() + eighths) % 8]
+    }
+
+    /// Turns 45° left.
+    pub fn turn_left(self) -> Self {
+        self.rotate(7)
+    }
+
+    /// Turns 45° right.
+    pub fn turn_right(self) -> Self {
+        self.rotate(1)
+    }
+
+    pub fn turn_around(self) -> Self {
+        self.rotate(4)
+    }
+
+    pub fn unit(self, y_axis: YAxis) -> IPoint2 {
+        let north = y_axis.north();
+        match self {
+            Dir8::North => IPoint2 { x: 0, y: north },
+            Dir8::NorthEast => IPoint2 { x: 1, y: north },
+            Dir8::East => IPoint2 { x: 1, y: 0 },
+            Dir8::SouthEast => IPoint2 { x: 1, y: -north },
+            Dir8::South => IPoint2 { x: 0, y: -north },
+            Dir8::SouthWest => IPoint2 { x: -1, y: -north },
+            Dir8::West => IPoint2 { x: -1, y: 0 },
+            Dir8::NorthWest => IPoint2 { x: -1, y: north },
+        }
+    }
+
+    /// The direction of a unit vector, or `None` if it isn't one.
+    pub fn from_unit(p: IPoint2, y_axis: YAxis) -> Option<Self> {
+        Self::variants()
+            .into_iter()
+            .find(|dir| dir.unit(y_axis) == p)
+    }
+}
+
+impl From<Dir> for Dir8 {
+    fn from(dir: Dir) -> Self {
+        match dir {
+            Dir::North => Dir8::North,
+            Dir::East => Dir8::East,
+            Dir::South => Dir8::South,
+            Dir::West => Dir8::West,
+        }
+    }
+}
+
+/// A position and a heading which can be moved and turned.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub struct Turtle {
+    pub at: IPoint2,
+    pub heading: Dir,
+    pub y_axis: YAxis,
+}
+
+impl Turtle {
+    pub fn new(at: IPoint2, heading: Dir, y_axis: YAxis) -> Self {
+        Self {
+            at,
+            heading,
+            y_axis,
+        }
+    }
+
+    /// The position one step forward.
+    pub fn ahead(&self) -> IPoint2 {
+        self.at + self.heading.unit(self.y_axis)
+    }
+
+    pub fn forward(&mut self, steps: isize) {
+        self.at += self.heading.unit(self.y_axis) * steps;
+    }
+
+    /// Moves in a direction without changing heading.
+    pub fn step(&mut self, dir: Dir) {
+        self.at += dir.unit(self.y_axis);
+    }
+
+    pub fn turn_left(&mut self) {
+        self.heading = self.heading.turn_left();
+    }
+
+    pub fn turn_right(&mut self) {
+        self.heading = self.heading.turn_right();
+    }
+
+    pub fn turn_around(&mut self) {
+        self.heading = self.heading.turn_around();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() -> Result<()> {
+        for (chars, dir) in &[
+            ("UN^", Dir::North),
+            ("RE>", Dir::East),
+            ("DSv", Dir::South),
+            ("LW<", Dir::West),
+        ] {
+            for c in chars.chars() {
+                assert_eq!(Dir::parse(c)?, *dir);
+            }
+            assert_eq!(Dir::parse(dir.arrow())?, *dir);
+        }
+        assert!(Dir::parse('x').is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_turns() {
+        for dir in Dir::variants() {
+            assert_eq!(dir.turn_left().turn_right(), dir);
+            assert_eq!(dir.turn_right().turn_right(), dir.turn_around());
+            assert_eq!(
+                Dir8::from(dir).turn_right().turn_right(),
+                dir.turn_right().into()
+            );
+        }
+        for dir in Dir8::variants() {
+            assert_eq!(dir.turn_left().turn_right(), dir);
+            assert_eq!(dir.turn_around().turn_around(), dir);
+        }
+    }
+
+    #[test]
+    fn test_units() {
+        for y_axis in &[YAxis::Down, YAxis::Up] {
+            for dir in Dir::variants() {
+                let unit = dir.unit(*y_axis);
+                assert_eq!(Dir::from_unit(unit, *y_axis), Some(dir));
+            }
+            for dir in Dir8::variants() {
+                assert_eq!(Dir8::from_unit(dir.unit(*y_axis), *y_axis), Some(dir));
+            }
+        }
+        for dir in Dir::variants() {
+            assert_eq!(
+                dir.turn_right().unit(YAxis::Down),
+                dir.unit(YAxis::Down).rotate_right()
+            );
+        }
+        assert_eq!(Dir::North.unit(YAxis::Down), IPoint2 { x: 0, y: -1 });
+        assert_eq!(Dir::North.unit(YAxis::Up), IPoint2 { x: 0, y: 1 });
+        assert_eq!(Dir::from_unit(IPoint2 { x: 1, y: 1 }, YAxis::Down), None);
+    }
+
+    #[test]
+    fn test_turtle() {
+        let mut turtle = Turtle::new(IPoint2::default(), Dir::North, YAxis::Down);
+        turtle.forward(2);
+        turtle.turn_right();
+        turtle.forward(3);
+        assert_eq!(turtle.at, IPoint2 { x: 3, y: -2 });
+        assert_eq!(turtle.ahead(), IPoint2 { x: 4, y: -2 });
+        turtle.turn_around();
+        turtle.step(Dir::South);
+        assert_eq!(turtle.at, IPoint2 { x: 3, y: -1 });
+        assert_eq!(turtle.heading, Dir::West);
+    }
+}
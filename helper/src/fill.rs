@@ -0,0 +1,163 @@
+use std::collections::HashSet;
+
+use crate::grid::{Grid, ORTHOGONAL};
+use crate::point::IPoint2;
+use crate::search::grid_successors;
+
+/// Fills outwards from `starts` one orthogonal step at a time through
+/// `passable` points, calling `on_step` with each step number and the points
+/// newly filled on that step. Step 0 is the starts themselves. Returns the
+/// number of steps taken to fill everything reachable.
+pub fn flood_fill<S, F, C>(starts: S, passable: F, mut on_step: C) -> usize
+where
+    S: IntoIterator<Item = IPoint2>,
+    F: Fn(IPoint2) -> bool,
+    C: FnMut(usize, &[IPoint2]),
+{
+    let mut filled: HashSet<IPoint2> = HashSet::new();
+    let mut frontier: Vec<IPoint2> = starts.into_iter().filter(|p| filled.insert(*p)).collect();
+    let mut steps = 0;
+    on_step(steps, &frontier);
+    loop {
+        let next: Vec<IPoint2> = frontier
+            .iter()
+            .flat_map(|p| grid_successors(*p, &passable))
+            .filter(|p| filled.insert(*p))
+            .collect();
+        if next.is_empty() {
+            return steps;
+        }
+        steps += 1;
+        on_step(steps, &next);
+        frontier = next;
+    }
+}
+
+/// An orthogonally connected set of points.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    pub points: HashSet<IPoint2>,
+}
+
+impl Region {
+    pub fn area(&self) -> usize {
+        self.points.len()
+    }
+
+    /// The number of edges between points in the region and points outside
+    /// it.
+    pub fn perimeter(&self) -> usize {
+        self.points
+            .iter()
+            .map(|p| {
+                ORTHOGONAL
+                    .iter()
+                    .filter(|o| !self.points.contains(&(*p + **o)))
+                    .count()
+            })
+            .sum()
+    }
+
+    pub fn contains(&self, p: IPoint2) -> bool {
+        self.points.contains(&p)
+    }
+}
+
+/// Splits a set of points into its orthogonally connected regions.
+pub fn regions<I>(points: I) -> Vec<Region>
+where
+    I: IntoIterator<Item = IPoint2>,
+{
+    let mut remaining: HashSet<IPoint2> = points.into_iter().collect();
+    let mut regions = vec![];
+    while let Some(start) = remaining.iter().next().cloned() {
+        let region = fill_region(start, |p| remaining.contains(&p));
+        for p in &region.points {
+            remaining.remove(p);
+        }
+        regions.push(region);
+    }
+    regions
+}
+
+/// Labels each cell of a grid with the index of the region it belongs to,
+/// regions being orthogonally connected cells of equal value.
+pub fn label<T>(grid: &Grid<T>) -> (Grid<usize>, Vec<Region>)
+where
+    T: PartialEq,
+{
+    let mut labels: Grid<Option<usize>> = grid.map(|_| None);
+    let mut regions = vec![];
+    for (p, t) in grid.iter() {
+        if labels[p].is_some() {
+            continue;
+        }
+        let region = fill_region(p, |n| grid.get(n) == Some(t));
+        for r in &region.points {
+            labels[*r] = Some(regions.len());
+        }
+        regions.push(region);
+    }
+    (labels.map(|l| l.unwrap_or_default()), regions)
+}
+
+fn fill_region<F>(start: IPoint2, passable: F) -> Region
+where
+    F: Fn(IPoint2) -> bool,
+{
+    let mut points = HashSet::new();
+    flood_fill(vec![start], passable, |_, filled| {
+        points.extend(filled.iter().cloned())
+    });
+    Region { points }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_flood_fill() -> Result<()> {
+        // The oxygen example from 2019 day 15
+        let maze = Grid::parse(" ##   \n#..## \n#.#..#\n#.O.# \n ###  ", |c| {
+            Ok(c == '.' || c == 'O')
+        })?;
+        let mut sizes = vec![];
+        let steps = flood_fill(
+            vec![IPoint2 { x: 2, y: 3 }],
+            |p| maze.get(p).cloned().unwrap_or(false),
+            |_, frontier| sizes.push(frontier.len()),
+        );
+        assert_eq!(steps, 4);
+        assert_eq!(sizes, vec![1, 2, 2, 2, 1]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_label() -> Result<()> {
+        let grid = Grid::parse("AAAA\nBBCD\nBBCC\nEEEC", Ok)?;
+        let (labels, regions) = label(&grid);
+        assert_eq!(regions.len(), 5);
+        let c = &regions[labels[IPoint2 { x: 2, y: 1 }]];
+        assert_eq!(c.area(), 4);
+        assert_eq!(c.perimeter(), 10);
+        let a = &regions[labels[IPoint2::default()]];
+        assert_eq!(a.area(), 4);
+        assert_eq!(a.perimeter(), 10);
+        Ok(())
+    }
+
+    #[test]
+    fn test_regions() {
+        let points = vec![
+            IPoint2 { x: 0, y: 0 },
+            IPoint2 { x: 1, y: 0 },
+            IPoint2 { x: 1, y: 1 },
+            IPoint2 { x: 3, y: 0 },
+        ];
+        let mut areas: Vec<usize> = regions(points).iter().map(|r| r.area()).collect();
+        areas.sort();
+        assert_eq!(areas, vec![1, 3]);
+    }
+}
@@ -0,0 +1,41 @@
+use crate::point::IPoint2;
+
+pub mod dense;
+pub mod sparse;
+
+pub use dense::Grid;
+pub use sparse::SparseGrid;
+
+pub const ORTHOGONAL: [IPoint2; 4] = [
+    IPoint2 { x: 0, y: -1 },
+    IPoint2 { x: 1, y: 0 },
+    IPoint2 { x: 0, y: 1 },
+    IPoint2 { x: -1, y: 0 },
+];
+
+pub const ADJACENT: [IPoint2; 8] = [
+    IPoint2 { x: 0, y: -1 },
+    IPoint2 { x: 1, y: -1 },
+    IPoint2 { x: 1, y: 0 },
+    IPoint2 { x: 1, y: 1 },
+    IPoint2 { x: 0, y: 1 },
+    IPoint2 { x: -1, y: 1 },
+    IPoint2 { x: -1, y: 0 },
+    IPoint2 { x: -1, y: -1 },
+];
+
+/// Finds the inclusive min and max corners of a set of points.
+pub fn bounds<'a, I>(points: I) -> Option<(IPoint2, IPoint2)>
+where
+    I: IntoIterator<Item = &'a IPoint2>,
+{
+    let mut points = points.into_iter();
+    let first = *points.next()?;
+    Some(points.fold((first, first), |(mut min, mut max), p| {
+        min.x = isize::min(min.x, p.x);
+        min.y = isize::min(min.y, p.y);
+        max.x = isize::max(max.x, p.x);
+        max.y = isize::max(max.y, p.y);
+        (min, max)
+    }))
+}
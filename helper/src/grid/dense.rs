@@ -0,0 +1,286 @@
+use anyhow::{anyhow, Result};
+
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use super::{ADJACENT, ORTHOGONAL};
+use crate::point::IPoint2;
+
+/// A fixed size grid with its top left corner at the origin.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Self> {
+        let height = rows.len();
+        let width = rows.first().map(|r| r.len()).unwrap_or(0);
+        let mut cells = Vec::with_capacity(width * height);
+        for (y, row) in rows.into_iter().enumerate() {
+            if row.len() != width {
+                return Err(anyhow!(
+                    "row {} has length {}, expected {}",
+                    y,
+                    row.len(),
+                    width
+                ));
+            }
+            cells.extend(row);
+        }
+        Ok(Self {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    /// Parses a grid from char art, one row per line, mapping each char with
+    /// `f`.
+    pub fn parse<F>(input: &str, f: F) -> Result<Self>
+    where
+        F: Fn(char) -> Result<T>,
+    {
+        Self::from_rows(
+            input
+                .lines()
+                .enumerate()
+                .map(|(y, line)| {
+                    line.chars()
+                        .enumerate()
+                        .map(|(x, c)| {
+                            f(c).map_err(|e| anyhow!("invalid char at ({},{}): {}", x, y, e))
+                        })
+                        .collect()
+                })
+                .collect::<Result<Vec<Vec<T>>>>()?,
+        )
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The inclusive min and max corners of the grid, or `None` if empty.
+    pub fn bounds(&self) -> Option<(IPoint2, IPoint2)> {
+        if self.cells.is_empty() {
+            return None;
+        }
+        Some((
+            IPoint2::default(),
+            IPoint2 {
+                x: self.width as isize - 1,
+                y: self.height as isize - 1,
+            },
+        ))
+    }
+
+    pub fn contains(&self, p: IPoint2) -> bool {
+        p.x >= 0 && p.y >= 0 && (p.x as usize) < self.width && (p.y as usize) < self.height
+    }
+
+    fn offset(&self, p: IPoint2) -> Option<usize> {
+        if self.contains(p) {
+            Some(p.y as usize * self.width + p.x as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, p: IPoint2) -> Option<&T> {
+        self.offset(p).map(|o| &self.cells[o])
+    }
+
+    pub fn get_mut(&mut self, p: IPoint2) -> Option<&mut T> {
+        self.offset(p).map(move |o| &mut self.cells[o])
+    }
+
+    /// Sets the value at `p`, returning the old value or `None` if `p` is out
+    /// of bounds.
+    pub fn set(&mut self, p: IPoint2, val: T) -> Option<T> {
+        self.get_mut(p).map(|cell| std::mem::replace(cell, val))
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = IPoint2> {
+        let width = self.width;
+        (0..self.cells.len()).map(move |i| IPoint2 {
+            x: (i % width) as isize,
+            y: (i / width) as isize,
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (IPoint2, &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (IPoint2, &mut T)> {
+        self.points().zip(self.cells.iter_mut())
+    }
+
+    /// The in bounds orthogonal neighbours of `p`.
+    pub fn neighbours4(&self, p: IPoint2) -> impl Iterator<Item = (IPoint2, &T)> {
+        self.neighbours(p, &ORTHOGONAL)
+    }
+
+    /// The in bounds orthogonal and diagonal neighbours of `p`.
+    pub fn neighbours8(&self, p: IPoint2) -> impl Iterator<Item = (IPoint2, &T)> {
+        self.neighbours(p, &ADJACENT)
+    }
+
+    fn neighbours<'a>(
+        &'a self,
+        p: IPoint2,
+        offsets: &'a [IPoint2],
+    ) -> impl Iterator<Item = (IPoint2, &'a T)> {
+        offsets.iter().filter_map(move |o| {
+            let n = p + *o;
+            self.get(n).map(|t| (n, t))
+        })
+    }
+
+    pub fn row(&self, y: usize) -> Option<&[T]> {
+        if y < self.height {
+            Some(&self.cells[y * self.width..(y + 1) * self.width])
+        } else {
+            None
+        }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(move |y| &self.cells[y * self.width..(y + 1) * self.width])
+    }
+
+    pub fn column(&self, x: usize) -> Option<impl Iterator<Item = &T>> {
+        if x < self.width {
+            Some(self.cells.iter().skip(x).step_by(self.width))
+        } else {
+            None
+        }
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |x| self.cells.iter().skip(x).step_by(self.width))
+    }
+
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: Fn(&T) -> U,
+    {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T> Index<IPoint2> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: IPoint2) -> &Self::Output {
+        match self.get(p) {
+            Some(t) => t,
+            None => panic!("{:?} out of bounds", p),
+        }
+    }
+}
+
+impl<T> IndexMut<IPoint2> for Grid<T> {
+    fn index_mut(&mut self, p: IPoint2) -> &mut Self::Output {
+        match self.get_mut(p) {
+            Some(t) => t,
+            None => panic!("{:?} out of bounds", p),
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for t in row {
+                write!(f, "{}", t)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_bool(c: char) -> Result<bool> {
+        match c {
+            '#' => Ok(true),
+            '.' => Ok(false),
+            _ => Err(anyhow!("invalid char {}", c)),
+        }
+    }
+
+    #[test]
+    fn test_parse_and_display() -> Result<()> {
+        let input = "#..\n.#.\n..#";
+        let grid = Grid::parse(input, parse_bool)?;
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid.height(), 3);
+        assert!(grid[IPoint2 { x: 1, y: 1 }]);
+        assert!(!grid[IPoint2 { x: 1, y: 0 }]);
+        assert_eq!(grid.map(|b| if *b { '#' } else { '.' }).to_string(), input);
+        assert!(Grid::parse("#.\n#", parse_bool).is_err());
+        assert!(Grid::parse("#x", parse_bool).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_neighbours() -> Result<()> {
+        let grid = Grid::parse("abc\ndef\nghi", Ok)?;
+        let corner: Vec<char> = grid
+            .neighbours4(IPoint2::default())
+            .map(|(_, c)| *c)
+            .collect();
+        assert_eq!(corner, vec!['b', 'd']);
+        assert_eq!(grid.neighbours4(IPoint2 { x: 1, y: 1 }).count(), 4);
+        assert_eq!(grid.neighbours8(IPoint2 { x: 1, y: 1 }).count(), 8);
+        assert_eq!(grid.neighbours8(IPoint2 { x: 2, y: 2 }).count(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rows_and_columns() -> Result<()> {
+        let grid = Grid::parse("abc\ndef", Ok)?;
+        assert_eq!(grid.row(1), Some(&['d', 'e', 'f'][..]));
+        assert_eq!(grid.row(2), None);
+        assert_eq!(grid.column(2).unwrap().cloned().collect::<String>(), "cf");
+        assert!(grid.column(3).is_none());
+        assert_eq!(
+            grid.columns()
+                .map(|c| c.cloned().collect::<String>())
+                .collect::<Vec<String>>(),
+            vec!["ad", "be", "cf"]
+        );
+        assert_eq!(
+            grid.bounds(),
+            Some((IPoint2::default(), IPoint2 { x: 2, y: 1 }))
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,237 @@
+use anyhow::{anyhow, Result};
+
+use std::collections::HashMap;
+use std::fmt;
+use std::iter::FromIterator;
+
+use super::{bounds, ADJACENT, ORTHOGONAL};
+use crate::point::IPoint2;
+
+/// An unbounded grid which only stores the points which have been set.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SparseGrid<T> {
+    tiles: HashMap<IPoint2, T>,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        Self {
+            tiles: HashMap::new(),
+        }
+    }
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses a grid from char art, one row per line, mapping each char with
+    /// `f`. Chars mapped to `None` are left unset.
+    pub fn parse<F>(input: &str, f: F) -> Result<Self>
+    where
+        F: Fn(char) -> Result<Option<T>>,
+    {
+        let mut grid = Self::new();
+        for (y, line) in input.lines().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                let t = f(c).map_err(|e| anyhow!("invalid char at ({},{}): {}", x, y, e))?;
+                if let Some(t) = t {
+                    grid.insert(
+                        IPoint2 {
+                            x: x as isize,
+                            y: y as isize,
+                        },
+                        t,
+                    );
+                }
+            }
+        }
+        Ok(grid)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// The inclusive min and max corners of the set points, or `None` if
+    /// empty.
+    pub fn bounds(&self) -> Option<(IPoint2, IPoint2)> {
+        bounds(self.tiles.keys())
+    }
+
+    pub fn contains(&self, p: IPoint2) -> bool {
+        self.tiles.contains_key(&p)
+    }
+
+    pub fn get(&self, p: IPoint2) -> Option<&T> {
+        self.tiles.get(&p)
+    }
+
+    pub fn get_mut(&mut self, p: IPoint2) -> Option<&mut T> {
+        self.tiles.get_mut(&p)
+    }
+
+    pub fn insert(&mut self, p: IPoint2, val: T) -> Option<T> {
+        self.tiles.insert(p, val)
+    }
+
+    pub fn remove(&mut self, p: IPoint2) -> Option<T> {
+        self.tiles.remove(&p)
+    }
+
+    pub fn entry(&mut self, p: IPoint2) -> std::collections::hash_map::Entry<'_, IPoint2, T> {
+        self.tiles.entry(p)
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = IPoint2> + '_ {
+        self.tiles.keys().cloned()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (IPoint2, &T)> {
+        self.tiles.iter().map(|(p, t)| (*p, t))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (IPoint2, &mut T)> {
+        self.tiles.iter_mut().map(|(p, t)| (*p, t))
+    }
+
+    /// The set orthogonal neighbours of `p`.
+    pub fn neighbours4(&self, p: IPoint2) -> impl Iterator<Item = (IPoint2, &T)> {
+        self.neighbours(p, &ORTHOGONAL)
+    }
+
+    /// The set orthogonal and diagonal neighbours of `p`.
+    pub fn neighbours8(&self, p: IPoint2) -> impl Iterator<Item = (IPoint2, &T)> {
+        self.neighbours(p, &ADJACENT)
+    }
+
+    fn neighbours<'a>(
+        &'a self,
+        p: IPoint2,
+        offsets: &'a [IPoint2],
+    ) -> impl Iterator<Item = (IPoint2, &'a T)> {
+        offsets.iter().filter_map(move |o| {
+            let n = p + *o;
+            self.get(n).map(|t| (n, t))
+        })
+    }
+
+    /// The set points in row `y`, ordered by x.
+    pub fn row(&self, y: isize) -> Vec<(IPoint2, &T)> {
+        let mut row: Vec<(IPoint2, &T)> = self.iter().filter(|(p, _)| p.y == y).collect();
+        row.sort_by_key(|(p, _)| p.x);
+        row
+    }
+
+    /// The set points in column `x`, ordered by y.
+    pub fn column(&self, x: isize) -> Vec<(IPoint2, &T)> {
+        let mut column: Vec<(IPoint2, &T)> = self.iter().filter(|(p, _)| p.x == x).collect();
+        column.sort_by_key(|(p, _)| p.y);
+        column
+    }
+
+    pub fn map<U, F>(&self, f: F) -> SparseGrid<U>
+    where
+        F: Fn(&T) -> U,
+    {
+        self.iter().map(|(p, t)| (p, f(t))).collect()
+    }
+}
+
+impl<T> FromIterator<(IPoint2, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (IPoint2, T)>>(iter: I) -> Self {
+        Self {
+            tiles: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<T> Extend<(IPoint2, T)> for SparseGrid<T> {
+    fn extend<I: IntoIterator<Item = (IPoint2, T)>>(&mut self, iter: I) {
+        self.tiles.extend(iter)
+    }
+}
+
+impl<T> From<HashMap<IPoint2, T>> for SparseGrid<T> {
+    fn from(tiles: HashMap<IPoint2, T>) -> Self {
+        Self { tiles }
+    }
+}
+
+/// Renders the area within the bounds, leaving unset points blank.
+impl<T: fmt::Display> fmt::Display for SparseGrid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (min, max) = match self.bounds() {
+            Some(b) => b,
+            None => return Ok(()),
+        };
+        for y in min.y..=max.y {
+            if y > min.y {
+                writeln!(f)?;
+            }
+            for x in min.x..=max.x {
+                match self.get(IPoint2 { x, y }) {
+                    Some(t) => write!(f, "{}", t)?,
+                    None => write!(f, " ")?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_wall(c: char) -> Result<Option<char>> {
+        match c {
+            '#' => Ok(Some('#')),
+            ' ' => Ok(None),
+            _ => Err(anyhow!("invalid char {}", c)),
+        }
+    }
+
+    #[test]
+    fn test_parse_and_display() -> Result<()> {
+        let input = "  #\n # \n#  ";
+        let grid = SparseGrid::parse(input, parse_wall)?;
+        assert_eq!(grid.len(), 3);
+        assert_eq!(
+            grid.bounds(),
+            Some((IPoint2::default(), IPoint2 { x: 2, y: 2 }))
+        );
+        assert_eq!(grid.to_string(), input);
+        assert_eq!(grid.neighbours4(IPoint2 { x: 1, y: 1 }).count(), 0);
+        assert_eq!(grid.neighbours8(IPoint2 { x: 1, y: 1 }).count(), 2);
+        assert!(SparseGrid::parse("#.", parse_wall).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_negative_bounds() {
+        let grid: SparseGrid<char> = vec![
+            (IPoint2 { x: -2, y: 1 }, 'a'),
+            (IPoint2 { x: 1, y: -1 }, 'b'),
+            (IPoint2 { x: 0, y: 1 }, 'c'),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            grid.bounds(),
+            Some((IPoint2 { x: -2, y: -1 }, IPoint2 { x: 1, y: 1 }))
+        );
+        assert_eq!(grid.to_string(), "   b\n    \na c ");
+        assert_eq!(
+            grid.row(1).into_iter().map(|(_, c)| *c).collect::<String>(),
+            "ac"
+        );
+        assert_eq!(grid.column(1).len(), 1);
+        assert_eq!(SparseGrid::<char>::new().bounds(), None);
+    }
+}
@@ -0,0 +1,267 @@
+use anyhow::{anyhow, Context, Result};
+
+use std::fs;
+use std::path::Path;
+
+use crate::grid::Grid;
+use crate::point::IPoint2;
+use crate::render::PointMap;
+
+/// A 24 bit colour.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash, Default)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+    pub const GREY: Rgb = Rgb(128, 128, 128);
+    pub const RED: Rgb = Rgb(255, 0, 0);
+    pub const GREEN: Rgb = Rgb(0, 255, 0);
+    pub const BLUE: Rgb = Rgb(0, 0, 255);
+}
+
+/// An RGB image which can be written as PPM or PNG.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, fill: Rgb) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![fill; width * height],
+        }
+    }
+
+    /// Draws each cell of a grid as a `scale` by `scale` block.
+    pub fn from_grid<T, F>(grid: &Grid<T>, palette: F, scale: usize) -> Self
+    where
+        F: Fn(&T) -> Rgb,
+    {
+        Self::from_map(grid, |t| t.map(&palette).unwrap_or_default(), scale)
+    }
+
+    /// Draws a point map sized to fit its points, with each point a `scale` by
+    /// `scale` block. The palette is passed `None` for points with no value.
+    pub fn from_map<T, M, F>(map: &M, palette: F, scale: usize) -> Self
+    where
+        M: PointMap<T>,
+        F: Fn(Option<&T>) -> Rgb,
+    {
+        let (min, max) = match map.point_bounds() {
+            Some(b) => b,
+            None => return Self::new(0, 0, Rgb::default()),
+        };
+        let size = max - min + IPoint2 { x: 1, y: 1 };
+        let mut image = Self::new(
+            size.x as usize * scale,
+            size.y as usize * scale,
+            Rgb::default(),
+        );
+        for y in 0..size.y {
+            for x in 0..size.x {
+                let colour = palette(map.get_point(min + IPoint2 { x, y }));
+                image.fill_rect(x as usize * scale, y as usize * scale, scale, scale, colour);
+            }
+        }
+        image
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<Rgb> {
+        if x < self.width && y < self.height {
+            Some(self.pixels[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    /// Sets a pixel, ignoring any outside the image.
+    pub fn set(&mut self, x: usize, y: usize, colour: Rgb) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = colour;
+        }
+    }
+
+    /// Fills a rectangle, clipped to the image.
+    pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, colour: Rgb) {
+        for py in y..(y + height).min(self.height) {
+            for px in x..(x + width).min(self.width) {
+                self.pixels[py * self.width + px] = colour;
+            }
+        }
+    }
+
+    fn raw(&self) -> impl Iterator<Item = u8> + '_ {
+        self.pixels.iter().flat_map(|p| vec![p.0, p.1, p.2])
+    }
+
+    /// Encodes as a binary (P6) PPM.
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut out = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        out.extend(self.raw());
+        out
+    }
+
+    /// Encodes as a PNG. The image data is stored uncompressed, which keeps
+    /// the output simple and reproducible at the cost of size. PNGs can't be
+    /// empty, so images without any pixels are an error.
+    pub fn to_png(&self) -> Result<Vec<u8>> {
+        if self.width == 0 || self.height == 0 {
+            return Err(anyhow!(
+                "can't encode a {}x{} image as PNG",
+                self.width,
+                self.height
+            ));
+        }
+        let mut header = vec![];
+        header.extend(&(self.width as u32).to_be_bytes());
+        header.extend(&(self.height as u32).to_be_bytes());
+        // 8 bit RGB, default compression, filter and no interlacing
+        header.extend(&[8, 2, 0, 0, 0]);
+
+        let mut scanlines = Vec::with_capacity((self.width * 3 + 1) * self.height);
+        let raw: Vec<u8> = self.raw().collect();
+        for row in raw.chunks(self.width * 3).take(self.height) {
+            // Each scanline starts with its filter type, 0 being none
+            scanlines.push(0);
+            scanlines.extend(row);
+        }
+
+        let mut out = vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+        png_chunk(&mut out, b"IHDR", &header);
+        png_chunk(&mut out, b"IDAT", &zlib_stored(&scanlines));
+        png_chunk(&mut out, b"IEND", &[]);
+        Ok(out)
+    }
+
+    /// Writes the image, picking the format from the `.png` or `.ppm`
+    /// extension.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let data = match path.extension().and_then(|e| e.to_str()) {
+            Some("png") => self.to_png()?,
+            Some("ppm") => self.to_ppm(),
+            _ => {
+                return Err(anyhow!(
+                    "unknown image format for {}, expected .png or .ppm",
+                    path.display()
+                ))
+            }
+        };
+        fs::write(path, data).with_context(|| format!("failed writing {}", path.display()))
+    }
+}
+
+fn png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    out.extend(data);
+    let crc = crc32(&out[start..]);
+    out.extend(&crc.to_be_bytes());
+}
+
+// Wraps data in a zlib stream of uncompressed deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(0xffff).peekable();
+    if blocks.peek().is_none() {
+        out.extend(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(last as u8);
+        out.extend(&len.to_le_bytes());
+        out.extend(&(!len).to_le_bytes());
+        out.extend(block);
+    }
+    out.extend(&adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, b| {
+        (0..8).fold(crc ^ *b as u32, |crc, _| {
+            if crc & 1 == 1 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), byte| {
+        let a = (a + *byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    b << 16 | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn test_from_grid() -> Result<()> {
+        let grid = Grid::parse("#.\n.#", |c| Ok(c == '#'))?;
+        let image = Image::from_grid(&grid, |lit| if *lit { Rgb::WHITE } else { Rgb::BLACK }, 2);
+        assert_eq!((image.width(), image.height()), (4, 4));
+        assert_eq!(image.get(1, 1), Some(Rgb::WHITE));
+        assert_eq!(image.get(2, 1), Some(Rgb::BLACK));
+        assert_eq!(image.get(3, 3), Some(Rgb::WHITE));
+        assert_eq!(image.get(4, 0), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ppm() {
+        let mut image = Image::new(2, 1, Rgb::BLACK);
+        image.set(1, 0, Rgb(1, 2, 3));
+        assert_eq!(
+            image.to_ppm(),
+            b"P6\n2 1\n255\n\x00\x00\x00\x01\x02\x03".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_png() -> Result<()> {
+        assert!(Image::new(0, 0, Rgb::RED).to_png().is_err());
+        assert!(Image::new(3, 0, Rgb::RED).to_png().is_err());
+
+        let png = Image::new(3, 2, Rgb::RED).to_png()?;
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(&png[16..24], &[0, 0, 0, 3, 0, 0, 0, 2]);
+        // IEND is always the same 12 bytes
+        assert_eq!(
+            &png[png.len() - 12..],
+            b"\x00\x00\x00\x00IEND\xae\x42\x60\x82"
+        );
+        // Stored block holding two scanlines of 1 + 3 * 3 bytes
+        let idat = &png[33..];
+        assert_eq!(&idat[4..8], b"IDAT");
+        assert_eq!(&idat[8..15], &[0x78, 0x01, 1, 20, 0, !20, 0xff]);
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+use anyhow::{anyhow, Context, Result};
+
+use std::collections::BTreeSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Environment variable for the input cache directory.
+pub const CACHE_DIR_ENV: &str = "AOC_CACHE_DIR";
+/// Environment variable for an input file to use instead of any other.
+pub const INPUT_ENV: &str = "AOC_INPUT";
+/// The name of the input used when none is given.
+pub const DEFAULT_NAME: &str = "input";
+
+/// Finds puzzle inputs by year, day and name. Inputs are looked for in order:
+///
+/// 1. The file named by `AOC_INPUT`, if set.
+/// 2. The cache directory, at `<cache>/<year>/day<DD>/<name>`.
+/// 3. The day's crates, at `<root>/<year>/day<DD>{a,b}/res/<name>`.
+///
+/// Named inputs let several people's inputs sit side by side, the default
+/// being `input`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inputs {
+    root: PathBuf,
+    cache_dir: Option<PathBuf>,
+    input_override: Option<PathBuf>,
+}
+
+impl Inputs {
+    /// Looks for inputs under the repository `root` only.
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        Self {
+            root: root.into(),
+            cache_dir: None,
+            input_override: None,
+        }
+    }
+
+    /// Looks for inputs under the repository `root`, also using the cache
+    /// directory and input file from the environment.
+    pub fn from_env<P: Into<PathBuf>>(root: P) -> Self {
+        Self {
+            root: root.into(),
+            cache_dir: env::var_os(CACHE_DIR_ENV).map(PathBuf::from),
+            input_override: env::var_os(INPUT_ENV).map(PathBuf::from),
+        }
+    }
+
+    pub fn cache_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.cache_dir = Some(dir.into());
+        self
+    }
+
+    fn cache_path(&self, year: usize, day: usize) -> Option<PathBuf> {
+        self.cache_dir
+            .as_ref()
+            .map(|dir| dir.join(year.to_string()).join(format!("day{:02}", day)))
+    }
+
+    fn res_paths(&self, year: usize, day: usize) -> Vec<PathBuf> {
+        ["a", "b"]
+            .iter()
+            .map(|part| {
+                self.root
+                    .join(year.to_string())
+                    .join(format!("day{:02}{}", day, part))
+                    .join("res")
+            })
+            .collect()
+    }
+
+    /// The path of a named input.
+    pub fn find(&self, year: usize, day: usize, name: &str) -> Result<PathBuf> {
+        if let Some(path) = &self.input_override {
+            return Ok(path.clone());
+        }
+        self.cache_path(year, day)
+            .into_iter()
+            .chain(self.res_paths(year, day))
+            .map(|dir| dir.join(name))
+            .find(|path| path.is_file())
+            .ok_or_else(|| anyhow!("no input {:?} for {} day {}", name, year, day))
+    }
+
+    pub fn read(&self, year: usize, day: usize, name: &str) -> Result<String> {
+        let path = self.find(year, day, name)?;
+        fs::read_to_string(&path).with_context(|| format!("failed reading {}", path.display()))
+    }
+
+    /// The names of all inputs available for a day, sorted.
+    pub fn names(&self, year: usize, day: usize) -> Result<Vec<String>> {
+        let mut names = BTreeSet::new();
+        for dir in self
+            .cache_path(year, day)
+            .into_iter()
+            .chain(self.res_paths(year, day))
+        {
+            if !dir.is_dir() {
+                continue;
+            }
+            for entry in fs::read_dir(&dir)? {
+                let entry = entry?;
+                if entry.file_type()?.is_file() {
+                    names.insert(entry.file_name().to_string_lossy().into_owned());
+                }
+            }
+        }
+        Ok(names.into_iter().collect())
+    }
+
+    /// Copies an input file into the cache under a name, returning where it
+    /// was stored.
+    pub fn store<P: AsRef<Path>>(
+        &self,
+        year: usize,
+        day: usize,
+        name: &str,
+        from: P,
+    ) -> Result<PathBuf> {
+        let dir = self
+            .cache_path(year, day)
+            .ok_or_else(|| anyhow!("no cache directory, set {}", CACHE_DIR_ENV))?;
+        fs::create_dir_all(&dir).with_context(|| format!("failed creating {}", dir.display()))?;
+        let to = dir.join(name);
+        fs::copy(from.as_ref(), &to).with_context(|| {
+            format!(
+                "failed copying {} to {}",
+                from.as_ref().display(),
+                to.display()
+            )
+        })?;
+        Ok(to)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Builds a repository and cache layout in a temporary directory
+    fn fixture(name: &str) -> Result<PathBuf> {
+        let dir = env::temp_dir().join(format!("helper-input-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        for (path, contents) in &[
+            ("root/2019/day04a/res/input", "res a"),
+            ("root/2019/day04b/res/input", "res b"),
+            ("root/2019/day04b/res/bob", "res bob"),
+            ("cache/2019/day04/alice", "cache alice"),
+            ("cache/2019/day04/bob", "cache bob"),
+        ] {
+            let path = dir.join(path);
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::write(path, contents)?;
+        }
+        Ok(dir)
+    }
+
+    #[test]
+    fn test_find() -> Result<()> {
+        let dir = fixture("find")?;
+        let inputs = Inputs::new(dir.join("root"));
+        assert_eq!(inputs.read(2019, 4, DEFAULT_NAME)?, "res a");
+        assert_eq!(inputs.read(2019, 4, "bob")?, "res bob");
+        assert!(inputs.read(2019, 4, "alice").is_err());
+        assert!(inputs.read(2019, 5, DEFAULT_NAME).is_err());
+
+        let inputs = inputs.cache_dir(dir.join("cache"));
+        assert_eq!(inputs.read(2019, 4, "alice")?, "cache alice");
+        assert_eq!(inputs.read(2019, 4, "bob")?, "cache bob");
+        assert_eq!(inputs.names(2019, 4)?, vec!["alice", "bob", "input"]);
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_store() -> Result<()> {
+        let dir = fixture("store")?;
+        let inputs = Inputs::new(dir.join("root"));
+        let from = dir.join("root/2019/day04a/res/input");
+        assert!(inputs.store(2019, 4, "carol", &from).is_err());
+        let inputs = inputs.cache_dir(dir.join("cache"));
+        inputs.store(2019, 1, "carol", &from)?;
+        assert_eq!(inputs.read(2019, 1, "carol")?, "res a");
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -1,9 +1,22 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use std::env;
 use std::error::Error;
+use std::fmt;
 use std::io::{BufRead, BufReader, Read};
 use std::str::FromStr;
 
+pub mod cycle;
+pub mod dir;
+pub mod fill;
+pub mod grid;
+pub mod image;
+pub mod input;
+pub mod ocr;
 pub mod point;
+pub mod record;
+pub mod render;
+pub mod scan;
+pub mod search;
 
 pub fn parse_lines<T, R>(input: R) -> impl Iterator<Item = Result<T>>
 where
@@ -21,3 +34,30 @@ where
 {
     Ok(line.parse()?)
 }
+
+/// The value following a `--name` command line argument, if given.
+pub fn arg_value(name: &str) -> Option<String> {
+    env::args()
+        .skip_while(|a| *a != format!("--{}", name))
+        .nth(1)
+}
+
+/// Parses the value following a `--name` command line argument, or returns
+/// `default` if it wasn't given.
+pub fn arg_parse<T>(name: &str, default: T) -> Result<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    match arg_value(name) {
+        Some(v) => v
+            .parse()
+            .map_err(|e| anyhow!("invalid --{} '{}': {}", name, v, e)),
+        None => Ok(default),
+    }
+}
+
+/// Whether a `--name` command line argument was given.
+pub fn has_arg(name: &str) -> bool {
+    env::args().any(|a| a == format!("--{}", name))
+}
@@ -0,0 +1,276 @@
+use anyhow::{anyhow, Result};
+
+use std::collections::HashSet;
+
+use crate::grid::{self, Grid};
+use crate::point::IPoint2;
+
+// The small font, letters are 4 wide (Y is 5) and 6 high
+const FONT_6: &[(char, &str)] = &[
+    ('A', ".##.\n#..#\n#..#\n####\n#..#\n#..#"),
+    ('B', "###.\n#..#\n###.\n#..#\n#..#\n###."),
+    ('C', ".##.\n#..#\n#...\n#...\n#..#\n.##."),
+    ('E', "####\n#...\n###.\n#...\n#...\n####"),
+    ('F', "####\n#...\n###.\n#...\n#...\n#..."),
+    ('G', ".##.\n#..#\n#...\n#.##\n#..#\n.###"),
+    ('H', "#..#\n#..#\n####\n#..#\n#..#\n#..#"),
+    ('I', ".###\n..#.\n..#.\n..#.\n..#.\n.###"),
+    ('J', "..##\n...#\n...#\n...#\n#..#\n.##."),
+    ('K', "#..#\n#.#.\n##..\n#.#.\n#.#.\n#..#"),
+    ('L', "#...\n#...\n#...\n#...\n#...\n####"),
+    ('O', ".##.\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('P', "###.\n#..#\n#..#\n###.\n#...\n#..."),
+    ('R', "###.\n#..#\n#..#\n###.\n#.#.\n#..#"),
+    ('S', ".###\n#...\n#...\n.##.\n...#\n###."),
+    ('U', "#..#\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('Y', "#...#\n#...#\n.#.#.\n..#..\n..#..\n..#.."),
+    ('Z', "####\n...#\n..#.\n.#..\n#...\n####"),
+];
+
+// The large font, letters are 6 wide and 10 high
+const FONT_10: &[(char, &str)] = &[
+    (
+        'A',
+        "..##..\n.#..#.\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#",
+    ),
+    (
+        'B',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#....#\n#....#\n#....#\n#....#\n#####.",
+    ),
+    (
+        'C',
+        ".####.\n#....#\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#....#\n.####.",
+    ),
+    (
+        'E',
+        "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n######",
+    ),
+    (
+        'F',
+        "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+    ),
+    (
+        'G',
+        ".####.\n#....#\n#.....\n#.....\n#.....\n#..###\n#....#\n#....#\n#...##\n.###.#",
+    ),
+    (
+        'H',
+        "#....#\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#\n#....#",
+    ),
+    (
+        'J',
+        "...###\n....#.\n....#.\n....#.\n....#.\n....#.\n....#.\n#...#.\n#...#.\n.###..",
+    ),
+    (
+        'K',
+        "#....#\n#...#.\n#..#..\n#.#...\n##....\n##....\n#.#...\n#..#..\n#...#.\n#....#",
+    ),
+    (
+        'L',
+        "#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n######",
+    ),
+    (
+        'N',
+        "#....#\n##...#\n##...#\n#.#..#\n#.#..#\n#..#.#\n#..#.#\n#...##\n#...##\n#....#",
+    ),
+    (
+        'P',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+    ),
+    (
+        'R',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#..#..\n#...#.\n#...#.\n#....#\n#....#",
+    ),
+    (
+        'X',
+        "#....#\n#....#\n.#..#.\n.#..#.\n..##..\n..##..\n.#..#.\n.#..#.\n#....#\n#....#",
+    ),
+    (
+        'Z',
+        "######\n.....#\n.....#\n....#.\n...#..\n..#...\n.#....\n#.....\n#.....\n######",
+    ),
+];
+
+// A glyph as lit columns, each a bitmask of lit rows
+type Glyph = Vec<u16>;
+
+fn parse_art(art: &str) -> HashSet<IPoint2> {
+    art.lines()
+        .enumerate()
+        .flat_map(|(y, line)| {
+            line.chars().enumerate().filter_map(move |(x, c)| {
+                if c == '#' || c == '█' {
+                    Some(IPoint2 {
+                        x: x as isize,
+                        y: y as isize,
+                    })
+                } else {
+                    None
+                }
+            })
+        })
+        .collect()
+}
+
+/// Splits lit points into glyphs on fully blank columns, with each glyph's
+/// blank edge columns trimmed.
+fn split_glyphs(lit: &HashSet<IPoint2>) -> Result<(usize, Vec<Glyph>)> {
+    let (min, max) = match grid::bounds(lit) {
+        Some(b) => b,
+        None => return Ok((0, vec![])),
+    };
+    let height = (max.y - min.y + 1) as usize;
+    if height > 16 {
+        return Err(anyhow!("text is {} high, too tall for any font", height));
+    }
+    let mut glyphs = vec![];
+    let mut current: Glyph = vec![];
+    for x in min.x..=max.x {
+        let column = (min.y..=max.y).fold(0, |mask, y| {
+            if lit.contains(&IPoint2 { x, y }) {
+                mask | 1 << (y - min.y)
+            } else {
+                mask
+            }
+        });
+        if column == 0 {
+            if !current.is_empty() {
+                glyphs.push(current);
+                current = vec![];
+            }
+        } else {
+            current.push(column);
+        }
+    }
+    if !current.is_empty() {
+        glyphs.push(current);
+    }
+    Ok((height, glyphs))
+}
+
+fn font(height: usize) -> Result<Vec<(char, Glyph)>> {
+    let font = match height {
+        6 => FONT_6,
+        10 => FONT_10,
+        _ => return Err(anyhow!("no font is {} high", height)),
+    };
+    font.iter()
+        .map(|(c, art)| Ok((*c, split_glyphs(&parse_art(art))?.1.remove(0))))
+        .collect()
+}
+
+/// Whether there's a font for letters `height` high.
+pub fn has_font(height: usize) -> bool {
+    font(height).is_ok()
+}
+
+fn render_glyph(glyph: &[u16], height: usize) -> String {
+    (0..height)
+        .map(|y| {
+            glyph
+                .iter()
+                .map(|col| if col & 1 << y > 0 { '#' } else { '.' })
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Reads the letters from a set of lit points.
+pub fn recognise_points<I>(lit: I) -> Result<String>
+where
+    I: IntoIterator<Item = IPoint2>,
+{
+    let (height, glyphs) = split_glyphs(&lit.into_iter().collect())?;
+    if glyphs.is_empty() {
+        return Ok(String::new());
+    }
+    let font = font(height)?;
+    glyphs
+        .iter()
+        .enumerate()
+        .map(|(index, glyph)| {
+            font.iter()
+                .find(|(_, g)| g == glyph)
+                .map(|(c, _)| *c)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "unrecognised glyph at letter {}:\n{}",
+                        index + 1,
+                        render_glyph(glyph, height)
+                    )
+                })
+        })
+        .collect()
+}
+
+/// Reads the letters from text art where `#` (or `█`) is lit and anything else
+/// is blank.
+pub fn recognise(art: &str) -> Result<String> {
+    recognise_points(parse_art(art))
+}
+
+/// Reads the letters from a grid, using `is_lit` to decide which cells are
+/// part of the letters.
+pub fn recognise_grid<T, F>(grid: &Grid<T>, is_lit: F) -> Result<String>
+where
+    F: Fn(&T) -> bool,
+{
+    recognise_points(grid.iter().filter(|(_, t)| is_lit(t)).map(|(p, _)| p))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recognise_small() -> Result<()> {
+        // 2019 day 8
+        let art = "
+#    ###  #### #  # #  #
+#    #  # #    # #  #  #
+#    #  # ###  ##   #  #
+#    ###  #    # #  #  #
+#    # #  #    # #  #  #
+#### #  # #    #  #  ##  ";
+        assert_eq!(recognise(art)?, "LRFKU");
+        Ok(())
+    }
+
+    #[test]
+    fn test_recognise_all() -> Result<()> {
+        for font in &[FONT_6, FONT_10] {
+            let art = font
+                .iter()
+                .map(|(_, art)| art.lines().collect::<Vec<&str>>())
+                .fold(
+                    vec![String::new(); font[0].1.lines().count()],
+                    |rows, lines| {
+                        rows.into_iter()
+                            .zip(lines)
+                            .map(|(row, line)| format!("{}..{}", row, line))
+                            .collect()
+                    },
+                )
+                .join("\n");
+            let expected: String = font.iter().map(|(c, _)| *c).collect();
+            assert_eq!(recognise(&art)?, expected);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_recognise_grid() -> Result<()> {
+        let grid = Grid::parse("#..#\n#..#\n####\n#..#\n#..#\n#..#", |c| Ok(c == '#'))?;
+        assert_eq!(recognise_grid(&grid, |lit| *lit)?, "H");
+        Ok(())
+    }
+
+    #[test]
+    fn test_unrecognised() {
+        let err = recognise("##\n##\n##\n##\n##\n##").unwrap_err();
+        assert!(err.to_string().contains("unrecognised glyph at letter 1"));
+        assert!(recognise("#\n#\n#").is_err());
+        assert_eq!(recognise("...").unwrap(), "");
+    }
+}
@@ -1,5 +1,17 @@
+pub mod ipoint;
 pub mod ipoint2;
 pub mod ipoint3;
 
+pub use ipoint::IPoint;
 pub use ipoint2::IPoint2;
 pub use ipoint3::IPoint3;
+
+pub(crate) fn gcd(a: isize, b: isize) -> isize {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    a
+}
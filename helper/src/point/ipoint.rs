@@ -0,0 +1,240 @@
+use std::ops::{
+    Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign,
+};
+
+use super::{IPoint2, IPoint3};
+
+/// An integer point with any number of dimensions.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Hash)]
+pub struct IPoint<const N: usize>(pub [isize; N]);
+
+impl<const N: usize> Default for IPoint<N> {
+    fn default() -> Self {
+        Self([0; N])
+    }
+}
+
+impl<const N: usize> From<[isize; N]> for IPoint<N> {
+    fn from(coords: [isize; N]) -> Self {
+        Self(coords)
+    }
+}
+
+impl From<IPoint2> for IPoint<2> {
+    fn from(p: IPoint2) -> Self {
+        Self([p.x, p.y])
+    }
+}
+
+impl From<IPoint<2>> for IPoint2 {
+    fn from(p: IPoint<2>) -> Self {
+        Self { x: p[0], y: p[1] }
+    }
+}
+
+impl From<IPoint3> for IPoint<3> {
+    fn from(p: IPoint3) -> Self {
+        Self([p.x, p.y, p.z])
+    }
+}
+
+impl From<IPoint<3>> for IPoint3 {
+    fn from(p: IPoint<3>) -> Self {
+        Self {
+            x: p[0],
+            y: p[1],
+            z: p[2],
+        }
+    }
+}
+
+impl<const N: usize> Add for IPoint<N> {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        self += other;
+        self
+    }
+}
+
+impl<const N: usize> AddAssign for IPoint<N> {
+    fn add_assign(&mut self, other: Self) {
+        for (a, b) in self.0.iter_mut().zip(other.0.iter()) {
+            *a += b;
+        }
+    }
+}
+
+impl<const N: usize> Sub for IPoint<N> {
+    type Output = Self;
+
+    fn sub(mut self, other: Self) -> Self {
+        self -= other;
+        self
+    }
+}
+
+impl<const N: usize> SubAssign for IPoint<N> {
+    fn sub_assign(&mut self, other: Self) {
+        for (a, b) in self.0.iter_mut().zip(other.0.iter()) {
+            *a -= b;
+        }
+    }
+}
+
+impl<const N: usize> Mul<isize> for IPoint<N> {
+    type Output = Self;
+
+    fn mul(mut self, scalar: isize) -> Self {
+        self *= scalar;
+        self
+    }
+}
+
+impl<const N: usize> MulAssign<isize> for IPoint<N> {
+    fn mul_assign(&mut self, scalar: isize) {
+        for a in self.0.iter_mut() {
+            *a *= scalar;
+        }
+    }
+}
+
+impl<const N: usize> Div<isize> for IPoint<N> {
+    type Output = Self;
+
+    fn div(mut self, scalar: isize) -> Self {
+        self /= scalar;
+        self
+    }
+}
+
+impl<const N: usize> DivAssign<isize> for IPoint<N> {
+    fn div_assign(&mut self, scalar: isize) {
+        for a in self.0.iter_mut() {
+            *a /= scalar;
+        }
+    }
+}
+
+impl<const N: usize> Neg for IPoint<N> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        self * -1
+    }
+}
+
+impl<const N: usize> Index<usize> for IPoint<N> {
+    type Output = isize;
+
+    fn index(&self, axis: usize) -> &Self::Output {
+        &self.0[axis]
+    }
+}
+
+impl<const N: usize> IndexMut<usize> for IPoint<N> {
+    fn index_mut(&mut self, axis: usize) -> &mut Self::Output {
+        &mut self.0[axis]
+    }
+}
+
+impl<const N: usize> IPoint<N> {
+    /// A unit vector along an axis.
+    pub fn unit(axis: usize) -> Self {
+        let mut p = Self::default();
+        p[axis] = 1;
+        p
+    }
+
+    pub fn length(&self) -> f64 {
+        (self.0.iter().map(|a| a.pow(2)).sum::<isize>() as f64).sqrt()
+    }
+
+    pub fn manhattan(&self) -> isize {
+        self.0.iter().map(|a| a.abs()).sum()
+    }
+
+    pub fn chebyshev(&self) -> isize {
+        self.0.iter().map(|a| a.abs()).max().unwrap_or(0)
+    }
+
+    pub fn dot(&self, other: Self) -> isize {
+        self.0.iter().zip(other.0.iter()).map(|(a, b)| a * b).sum()
+    }
+
+    pub fn signum(&self) -> Self {
+        let mut p = *self;
+        for a in p.0.iter_mut() {
+            *a = a.signum();
+        }
+        p
+    }
+
+    /// The 2N points one step away along a single axis.
+    pub fn orthogonal_neighbours(&self) -> impl Iterator<Item = Self> {
+        let p = *self;
+        (0..N).flat_map(move |axis| {
+            let unit = Self::unit(axis);
+            vec![p - unit, p + unit]
+        })
+    }
+
+    /// The 3^N - 1 points surrounding this one, including diagonals.
+    pub fn neighbours(&self) -> impl Iterator<Item = Self> {
+        let p = *self;
+        (0..3usize.pow(N as u32)).filter_map(move |mut i| {
+            let mut offset = Self::default();
+            for a in offset.0.iter_mut() {
+                *a = (i % 3) as isize - 1;
+                i /= 3;
+            }
+            if offset == Self::default() {
+                None
+            } else {
+                Some(p + offset)
+            }
+        })
+    }
+
+    pub fn to_vec(&self) -> Vec<isize> {
+        self.0.to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic() {
+        let a = IPoint([1, -2, 3, 4]);
+        let b = IPoint([2, 2, 2, 2]);
+        assert_eq!(a + b, IPoint([3, 0, 5, 6]));
+        assert_eq!(a - b, IPoint([-1, -4, 1, 2]));
+        assert_eq!(a * 2, IPoint([2, -4, 6, 8]));
+        assert_eq!(-a / 1, IPoint([-1, 2, -3, -4]));
+        assert_eq!(a.manhattan(), 10);
+        assert_eq!(a.chebyshev(), 4);
+        assert_eq!(a.dot(b), 12);
+        assert_eq!(IPoint([3, 4]).length(), 5.0);
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let p = IPoint([5, 5, 5]);
+        assert_eq!(p.orthogonal_neighbours().count(), 6);
+        assert!(p.orthogonal_neighbours().all(|n| (n - p).manhattan() == 1));
+        assert_eq!(p.neighbours().count(), 26);
+        assert_eq!(IPoint([0, 0, 0, 0]).neighbours().count(), 80);
+        assert!(!p.neighbours().any(|n| n == p));
+    }
+
+    #[test]
+    fn test_conversions() {
+        let p2 = IPoint2 { x: 1, y: 2 };
+        assert_eq!(IPoint2::from(IPoint::from(p2)), p2);
+        let p3 = IPoint3 { x: 1, y: 2, z: 3 };
+        assert_eq!(IPoint::from(p3), IPoint([1, 2, 3]));
+        assert_eq!(IPoint3::from(IPoint::from(p3)), p3);
+    }
+}
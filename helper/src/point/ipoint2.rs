@@ -1,6 +1,10 @@
-use std::ops::{Add, AddAssign, Index, Sub, SubAssign};
+use std::ops::{
+    Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign,
+};
 
-#[derive(Debug, PartialEq, Eq, Copy, Clone, Default, Hash)]
+use super::gcd;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Default, Hash)]
 pub struct IPoint2 {
     pub x: isize,
     pub y: isize,
@@ -42,6 +46,59 @@ impl SubAssign for IPoint2 {
     }
 }
 
+impl Mul<isize> for IPoint2 {
+    type Output = Self;
+
+    fn mul(self, scalar: isize) -> Self {
+        Self {
+            x: self.x * scalar,
+            y: self.y * scalar,
+        }
+    }
+}
+
+impl MulAssign<isize> for IPoint2 {
+    fn mul_assign(&mut self, scalar: isize) {
+        self.x *= scalar;
+        self.y *= scalar;
+    }
+}
+
+impl Div<isize> for IPoint2 {
+    type Output = Self;
+
+    fn div(self, scalar: isize) -> Self {
+        Self {
+            x: self.x / scalar,
+            y: self.y / scalar,
+        }
+    }
+}
+
+impl DivAssign<isize> for IPoint2 {
+    fn div_assign(&mut self, scalar: isize) {
+        self.x /= scalar;
+        self.y /= scalar;
+    }
+}
+
+impl Neg for IPoint2 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            x: -self.x,
+            y: -self.y,
+        }
+    }
+}
+
+impl From<(isize, isize)> for IPoint2 {
+    fn from((x, y): (isize, isize)) -> Self {
+        Self { x, y }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
 pub enum Field {
     X,
@@ -59,6 +116,15 @@ impl Index<Field> for IPoint2 {
     }
 }
 
+impl IndexMut<Field> for IPoint2 {
+    fn index_mut(&mut self, field: Field) -> &mut Self::Output {
+        match field {
+            Field::X => &mut self.x,
+            Field::Y => &mut self.y,
+        }
+    }
+}
+
 impl IPoint2 {
     pub fn length(&self) -> f64 {
         ((self.x.pow(2) + self.y.pow(2)) as f64).sqrt()
@@ -68,7 +134,96 @@ impl IPoint2 {
         self.x.abs() + self.y.abs()
     }
 
+    pub fn chebyshev(&self) -> isize {
+        isize::max(self.x.abs(), self.y.abs())
+    }
+
+    pub fn dot(&self, other: Self) -> isize {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// The z component of the 3D cross product, positive when `other` is
+    /// clockwise of `self` with y pointing down.
+    pub fn cross(&self, other: Self) -> isize {
+        self.x * other.y - self.y * other.x
+    }
+
+    pub fn signum(&self) -> Self {
+        Self {
+            x: self.x.signum(),
+            y: self.y.signum(),
+        }
+    }
+
+    /// The smallest whole vector pointing in the same direction, found by
+    /// dividing by the gcd of the components.
+    pub fn direction(&self) -> Self {
+        match gcd(self.x, self.y) {
+            0 => *self,
+            d => *self / d,
+        }
+    }
+
+    /// Rotates 90° left, with y pointing down.
+    pub fn rotate_left(&self) -> Self {
+        Self {
+            x: self.y,
+            y: -self.x,
+        }
+    }
+
+    /// Rotates 90° right, with y pointing down.
+    pub fn rotate_right(&self) -> Self {
+        Self {
+            x: -self.y,
+            y: self.x,
+        }
+    }
+
     pub fn to_vec(&self) -> Vec<isize> {
         vec![self.x, self.y]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic() {
+        let p = IPoint2 { x: 3, y: -4 };
+        assert_eq!(p * 2, IPoint2 { x: 6, y: -8 });
+        assert_eq!(p / 2, IPoint2 { x: 1, y: -2 });
+        assert_eq!(-p, IPoint2 { x: -3, y: 4 });
+        assert_eq!(p.dot(IPoint2 { x: 2, y: 1 }), 2);
+        assert_eq!(p.cross(IPoint2 { x: 2, y: 1 }), 11);
+        assert_eq!(p.signum(), IPoint2 { x: 1, y: -1 });
+        assert_eq!(p.manhattan(), 7);
+        assert_eq!(p.chebyshev(), 4);
+        assert_eq!(IPoint2::from((3, -4)), p);
+    }
+
+    #[test]
+    fn test_direction() {
+        assert_eq!(IPoint2 { x: 6, y: -4 }.direction(), IPoint2 { x: 3, y: -2 });
+        assert_eq!(IPoint2 { x: 0, y: 5 }.direction(), IPoint2 { x: 0, y: 1 });
+        assert_eq!(IPoint2 { x: -7, y: 0 }.direction(), IPoint2 { x: -1, y: 0 });
+        assert_eq!(IPoint2::default().direction(), IPoint2::default());
+    }
+
+    #[test]
+    fn test_rotate() {
+        let up = IPoint2 { x: 0, y: -1 };
+        assert_eq!(up.rotate_left(), IPoint2 { x: -1, y: 0 });
+        assert_eq!(up.rotate_right(), IPoint2 { x: 1, y: 0 });
+        assert_eq!(up.rotate_left().rotate_left(), -up);
+        assert_eq!(up.rotate_right().rotate_left(), up);
+    }
+
+    #[test]
+    fn test_index_mut() {
+        let mut p = IPoint2::default();
+        p[Field::Y] = 3;
+        assert_eq!(p, IPoint2 { x: 0, y: 3 });
+    }
+}
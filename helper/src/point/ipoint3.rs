@@ -1,6 +1,10 @@
-use std::ops::{Add, AddAssign, Index, Sub, SubAssign};
+use std::ops::{
+    Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign,
+};
 
-#[derive(Debug, PartialEq, Eq, Copy, Clone, Default, Hash)]
+use super::gcd;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Default, Hash)]
 pub struct IPoint3 {
     pub x: isize,
     pub y: isize,
@@ -47,6 +51,64 @@ impl SubAssign for IPoint3 {
     }
 }
 
+impl Mul<isize> for IPoint3 {
+    type Output = Self;
+
+    fn mul(self, scalar: isize) -> Self {
+        Self {
+            x: self.x * scalar,
+            y: self.y * scalar,
+            z: self.z * scalar,
+        }
+    }
+}
+
+impl MulAssign<isize> for IPoint3 {
+    fn mul_assign(&mut self, scalar: isize) {
+        self.x *= scalar;
+        self.y *= scalar;
+        self.z *= scalar;
+    }
+}
+
+impl Div<isize> for IPoint3 {
+    type Output = Self;
+
+    fn div(self, scalar: isize) -> Self {
+        Self {
+            x: self.x / scalar,
+            y: self.y / scalar,
+            z: self.z / scalar,
+        }
+    }
+}
+
+impl DivAssign<isize> for IPoint3 {
+    fn div_assign(&mut self, scalar: isize) {
+        self.x /= scalar;
+        self.y /= scalar;
+        self.z /= scalar;
+    }
+}
+
+impl Neg for IPoint3 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+}
+
+impl From<(isize, isize, isize)> for IPoint3 {
+    fn from((x, y, z): (isize, isize, isize)) -> Self {
+        Self { x, y, z }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
 pub enum Field {
     X,
@@ -66,6 +128,16 @@ impl Index<Field> for IPoint3 {
     }
 }
 
+impl IndexMut<Field> for IPoint3 {
+    fn index_mut(&mut self, field: Field) -> &mut Self::Output {
+        match field {
+            Field::X => &mut self.x,
+            Field::Y => &mut self.y,
+            Field::Z => &mut self.z,
+        }
+    }
+}
+
 impl IPoint3 {
     pub fn length(&self) -> f64 {
         ((self.x.pow(2) + self.y.pow(2) + self.z.pow(2)) as f64).sqrt()
@@ -75,7 +147,60 @@ impl IPoint3 {
         self.x.abs() + self.y.abs() + self.z.abs()
     }
 
+    pub fn chebyshev(&self) -> isize {
+        isize::max(self.x.abs(), isize::max(self.y.abs(), self.z.abs()))
+    }
+
+    pub fn dot(&self, other: Self) -> isize {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(&self, other: Self) -> Self {
+        Self {
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
+        }
+    }
+
+    pub fn signum(&self) -> Self {
+        Self {
+            x: self.x.signum(),
+            y: self.y.signum(),
+            z: self.z.signum(),
+        }
+    }
+
+    /// The smallest whole vector pointing in the same direction, found by
+    /// dividing by the gcd of the components.
+    pub fn direction(&self) -> Self {
+        match gcd(gcd(self.x, self.y), self.z) {
+            0 => *self,
+            d => *self / d,
+        }
+    }
+
     pub fn to_vec(&self) -> Vec<isize> {
         vec![self.x, self.y, self.z]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic() {
+        let p = IPoint3 { x: 2, y: -4, z: 6 };
+        assert_eq!(p * -1, -p);
+        assert_eq!(p / 2, IPoint3 { x: 1, y: -2, z: 3 });
+        assert_eq!(p.direction(), IPoint3 { x: 1, y: -2, z: 3 });
+        assert_eq!(p.dot(IPoint3 { x: 1, y: 1, z: 1 }), 4);
+        assert_eq!(p.chebyshev(), 6);
+        assert_eq!(
+            IPoint3 { x: 1, y: 0, z: 0 }.cross(IPoint3 { x: 0, y: 1, z: 0 }),
+            IPoint3 { x: 0, y: 0, z: 1 }
+        );
+        assert_eq!(IPoint3::from((2, -4, 6)), p);
+    }
+}
@@ -0,0 +1,392 @@
+use anyhow::{anyhow, Context, Result};
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::image::{Image, Rgb};
+
+#[derive(Debug, Clone)]
+enum Output {
+    Gif(PathBuf, Gif),
+    PpmSequence(PathBuf),
+}
+
+/// Records frames of a simulation as it runs. Call `step` once per step of
+/// the simulation and `finish` at the end.
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    output: Output,
+    every: usize,
+    max_frames: Option<usize>,
+    steps: usize,
+    recorded: usize,
+}
+
+impl Recorder {
+    /// Records to an animated GIF if the path ends in `.gif`, otherwise to a
+    /// numbered PPM sequence with the path as the directory.
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        let path = path.into();
+        let output = match path.extension().and_then(|e| e.to_str()) {
+            Some("gif") => Output::Gif(path, Gif::new(4)),
+            _ => Output::PpmSequence(path),
+        };
+        Self {
+            output,
+            every: 1,
+            max_frames: None,
+            steps: 0,
+            recorded: 0,
+        }
+    }
+
+    /// Only records every nth step, skipping the rest.
+    pub fn every(mut self, n: usize) -> Self {
+        self.every = n.max(1);
+        self
+    }
+
+    /// Stops recording after this many frames.
+    pub fn max_frames(mut self, n: usize) -> Self {
+        self.max_frames = Some(n);
+        self
+    }
+
+    /// The delay between GIF frames in hundredths of a second.
+    pub fn delay(mut self, hundredths: u16) -> Self {
+        if let Output::Gif(_, gif) = &mut self.output {
+            gif.delay = hundredths;
+        }
+        self
+    }
+
+    /// Whether the frame limit has been reached.
+    pub fn is_full(&self) -> bool {
+        self.max_frames.map(|m| self.recorded >= m).unwrap_or(false)
+    }
+
+    /// Advances a step, only calling `render` if the step is being recorded.
+    pub fn step<F>(&mut self, render: F) -> Result<()>
+    where
+        F: FnOnce() -> Image,
+    {
+        let step = self.steps;
+        self.steps += 1;
+        if !step.is_multiple_of(self.every) || self.is_full() {
+            return Ok(());
+        }
+        let frame = render();
+        match &mut self.output {
+            Output::Gif(_, gif) => gif.push(&frame)?,
+            Output::PpmSequence(dir) => {
+                fs::create_dir_all(&dir)
+                    .with_context(|| format!("failed creating {}", dir.display()))?;
+                frame.save(dir.join(format!("{:05}.ppm", self.recorded)))?;
+            }
+        }
+        self.recorded += 1;
+        Ok(())
+    }
+
+    /// Writes out anything still buffered, returning the number of frames
+    /// recorded.
+    pub fn finish(self) -> Result<usize> {
+        if let Output::Gif(path, gif) = self.output {
+            fs::write(&path, gif.finish())
+                .with_context(|| format!("failed writing {}", path.display()))?;
+        }
+        Ok(self.recorded)
+    }
+}
+
+/// An animated GIF encoder. Frames are compressed as they are pushed, each
+/// with its own colour table of up to 256 colours. Frames may differ in size,
+/// the canvas being the largest of them.
+#[derive(Debug, Clone)]
+pub struct Gif {
+    /// The delay between frames in hundredths of a second.
+    pub delay: u16,
+    width: usize,
+    height: usize,
+    frames: Vec<u8>,
+}
+
+impl Gif {
+    pub fn new(delay: u16) -> Self {
+        Self {
+            delay,
+            width: 0,
+            height: 0,
+            frames: vec![],
+        }
+    }
+
+    pub fn push(&mut self, frame: &Image) -> Result<()> {
+        if frame.width() > 0xffff || frame.height() > 0xffff {
+            return Err(anyhow!(
+                "{}x{} is too large for a GIF",
+                frame.width(),
+                frame.height()
+            ));
+        }
+        let mut palette: Vec<Rgb> = vec![];
+        let mut indexes: HashMap<Rgb, u8> = HashMap::new();
+        let mut pixels = Vec::with_capacity(frame.width() * frame.height());
+        for y in 0..frame.height() {
+            for x in 0..frame.width() {
+                let colour = frame.get(x, y).unwrap();
+                let index = match indexes.get(&colour) {
+                    Some(i) => *i,
+                    None if palette.len() < 256 => {
+                        let i = palette.len() as u8;
+                        palette.push(colour);
+                        indexes.insert(colour, i);
+                        i
+                    }
+                    None => return Err(anyhow!("frame uses more than 256 colours")),
+                };
+                pixels.push(index);
+            }
+        }
+        // The colour table must have a power of two size, at least 2
+        let bits = (1..=8).find(|b| 1 << b >= palette.len()).unwrap();
+        palette.resize(1 << bits, Rgb::default());
+
+        let out = &mut self.frames;
+        // Graphic control, clearing to the background between frames
+        out.extend(&[0x21, 0xf9, 4, 0x08]);
+        out.extend(&self.delay.to_le_bytes());
+        out.extend(&[0, 0]);
+        // Image descriptor at the top left with a local colour table
+        out.extend(&[0x2c, 0, 0, 0, 0]);
+        out.extend(&(frame.width() as u16).to_le_bytes());
+        out.extend(&(frame.height() as u16).to_le_bytes());
+        out.push(0x80 | (bits as u8 - 1));
+        for colour in &palette {
+            out.extend(&[colour.0, colour.1, colour.2]);
+        }
+        let min_code_size = bits.max(2) as u8;
+        out.push(min_code_size);
+        for block in lzw(&pixels, min_code_size).chunks(255) {
+            out.push(block.len() as u8);
+            out.extend(block);
+        }
+        out.push(0);
+        self.width = self.width.max(frame.width());
+        self.height = self.height.max(frame.height());
+        Ok(())
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        let mut out = b"GIF89a".to_vec();
+        out.extend(&(self.width as u16).to_le_bytes());
+        out.extend(&(self.height as u16).to_le_bytes());
+        // No global colour table
+        out.extend(&[0, 0, 0]);
+        // Loop forever
+        out.extend(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00");
+        out.extend(self.frames);
+        out.push(0x3b);
+        out
+    }
+}
+
+// Packs variable width codes least significant bit first
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u32,
+    len: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u32) {
+        self.acc |= (code as u32) << self.len;
+        self.len += size;
+        while self.len >= 8 {
+            self.bytes.push(self.acc as u8);
+            self.acc >>= 8;
+            self.len -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.len > 0 {
+            self.bytes.push(self.acc as u8);
+        }
+        self.bytes
+    }
+}
+
+fn lzw(pixels: &[u8], min_code_size: u8) -> Vec<u8> {
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+    let mut out = BitWriter::default();
+    let mut dict: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut size = min_code_size as u32 + 1;
+    let mut next = end + 1;
+    out.write(clear, size);
+
+    let mut pixels = pixels.iter();
+    let mut current = match pixels.next() {
+        Some(p) => *p as u16,
+        None => {
+            out.write(end, size);
+            return out.finish();
+        }
+    };
+    for p in pixels {
+        if let Some(code) = dict.get(&(current, *p)) {
+            current = *code;
+            continue;
+        }
+        out.write(current, size);
+        if next == 4096 {
+            // The table is full, start again
+            out.write(clear, size);
+            dict.clear();
+            size = min_code_size as u32 + 1;
+            next = end + 1;
+        } else {
+            dict.insert((current, *p), next);
+            next += 1;
+            // The decoder adds codes a step behind, so widens a step later
+            if next > 1 << size && size < 12 {
+                size += 1;
+            }
+        }
+        current = *p as u16;
+    }
+    out.write(current, size);
+    // Reading the last code fills the decoder's table up to ours
+    if next == 1 << size && size < 12 {
+        size += 1;
+    }
+    out.write(end, size);
+    out.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A straightforward decoder following the GIF spec
+    fn unlzw(data: &[u8], min_code_size: u8) -> Vec<u8> {
+        let clear = 1usize << min_code_size;
+        let mut table: Vec<Vec<u8>> = vec![];
+        let mut size = min_code_size as u32 + 1;
+        let mut prev: Option<Vec<u8>> = None;
+        let mut out = vec![];
+        let (mut acc, mut len) = (0u32, 0);
+        let mut bytes = data.iter();
+        loop {
+            while len < size {
+                acc |= (*bytes.next().unwrap() as u32) << len;
+                len += 8;
+            }
+            let code = (acc & ((1 << size) - 1)) as usize;
+            acc >>= size;
+            len -= size;
+            if code == clear {
+                table = (0..clear).map(|i| vec![i as u8]).collect();
+                table.push(vec![]);
+                table.push(vec![]);
+                size = min_code_size as u32 + 1;
+                prev = None;
+                continue;
+            }
+            if code == clear + 1 {
+                return out;
+            }
+            let entry = match (table.get(code), &prev) {
+                (Some(e), _) => e.clone(),
+                (None, Some(p)) => {
+                    let mut e = p.clone();
+                    e.push(p[0]);
+                    e
+                }
+                (None, None) => panic!("invalid code"),
+            };
+            if let Some(mut p) = prev {
+                p.push(entry[0]);
+                table.push(p);
+                if table.len() == 1 << size && size < 12 {
+                    size += 1;
+                }
+            }
+            out.extend(&entry);
+            prev = Some(entry);
+        }
+    }
+
+    #[test]
+    fn test_lzw() {
+        let mut seed = 1u32;
+        let pixels: Vec<u8> = (0..20_000)
+            .map(|i| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                if i % 3 == 0 {
+                    (seed >> 16) as u8 % 16
+                } else {
+                    (i / 50 % 4) as u8
+                }
+            })
+            .collect();
+        assert_eq!(unlzw(&lzw(&pixels, 4), 4), pixels);
+        for len in 0..pixels.len().min(1000) {
+            assert_eq!(unlzw(&lzw(&pixels[..len], 4), 4), &pixels[..len]);
+        }
+        assert_eq!(unlzw(&lzw(&[1, 0, 1, 1], 2), 2), vec![1, 0, 1, 1]);
+        assert_eq!(unlzw(&lzw(&[], 2), 2), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_gif() -> Result<()> {
+        let mut gif = Gif::new(10);
+        gif.push(&Image::new(2, 3, Rgb::BLACK))?;
+        let mut frame = Image::new(4, 1, Rgb::WHITE);
+        frame.set(0, 0, Rgb::RED);
+        frame.set(1, 0, Rgb::BLUE);
+        gif.push(&frame)?;
+        let data = gif.finish();
+        assert_eq!(&data[..6], b"GIF89a");
+        assert_eq!(&data[6..10], &[4, 0, 3, 0]);
+        // The first frame has a two colour table, the second four
+        let first = 32;
+        assert_eq!(&data[first..first + 2], &[0x21, 0xf9]);
+        assert_eq!(
+            &data[first + 8..first + 18],
+            &[0x2c, 0, 0, 0, 0, 2, 0, 3, 0, 0x80]
+        );
+        assert_eq!(&data[first + 18..first + 24], &[0, 0, 0, 0, 0, 0]);
+        assert_eq!(data[data.len() - 1], 0x3b);
+
+        let mut too_many = Image::new(257, 1, Rgb::BLACK);
+        for x in 0..257 {
+            too_many.set(x, 0, Rgb(x as u8, (x / 256) as u8, 0));
+        }
+        assert!(Gif::new(10).push(&too_many).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_recorder() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("helper-record-{}", std::process::id()));
+        let mut recorder = Recorder::new(&dir).every(3).max_frames(2);
+        let mut rendered = 0;
+        for _ in 0..10 {
+            recorder.step(|| {
+                rendered += 1;
+                Image::new(1, 1, Rgb::RED)
+            })?;
+        }
+        assert!(recorder.is_full());
+        assert_eq!(recorder.finish()?, 2);
+        assert_eq!(rendered, 2);
+        assert!(dir.join("00001.ppm").exists());
+        assert!(!dir.join("00002.ppm").exists());
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::BuildHasher;
+
+use crate::grid::{self, Grid, SparseGrid};
+use crate::point::IPoint2;
+
+/// The standard eight ANSI terminal colours.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub enum Colour {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+impl Colour {
+    fn ansi_code(self) -> usize {
+        30 + match self {
+            Colour::Black => 0,
+            Colour::Red => 1,
+            Colour::Green => 2,
+            Colour::Yellow => 3,
+            Colour::Blue => 4,
+            Colour::Magenta => 5,
+            Colour::Cyan => 6,
+            Colour::White => 7,
+        }
+    }
+}
+
+/// A single rendered cell, the colour only being used for ANSI output.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub struct Glyph {
+    pub ch: char,
+    pub colour: Option<Colour>,
+}
+
+impl Glyph {
+    pub fn new(ch: char, colour: Colour) -> Self {
+        Self {
+            ch,
+            colour: Some(colour),
+        }
+    }
+}
+
+impl From<char> for Glyph {
+    fn from(ch: char) -> Self {
+        Self { ch, colour: None }
+    }
+}
+
+/// Decides how each value is drawn. Any `Fn(&T) -> impl Into<Glyph>` is a
+/// palette, so a closure returning a `char` is enough for plain text.
+pub trait Palette<T> {
+    fn glyph(&self, val: &T) -> Glyph;
+}
+
+impl<T, F, G> Palette<T> for F
+where
+    F: Fn(&T) -> G,
+    G: Into<Glyph>,
+{
+    fn glyph(&self, val: &T) -> Glyph {
+        self(val).into()
+    }
+}
+
+/// Draws values using the first char of their `Display` output.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub struct DisplayPalette;
+
+impl<T: fmt::Display> Palette<T> for DisplayPalette {
+    fn glyph(&self, val: &T) -> Glyph {
+        val.to_string().chars().next().unwrap_or(' ').into()
+    }
+}
+
+/// Anything which can look up values by point and knows the bounds of its
+/// points.
+pub trait PointMap<T> {
+    fn get_point(&self, p: IPoint2) -> Option<&T>;
+    fn point_bounds(&self) -> Option<(IPoint2, IPoint2)>;
+}
+
+impl<T, S: BuildHasher> PointMap<T> for HashMap<IPoint2, T, S> {
+    fn get_point(&self, p: IPoint2) -> Option<&T> {
+        self.get(&p)
+    }
+
+    fn point_bounds(&self) -> Option<(IPoint2, IPoint2)> {
+        grid::bounds(self.keys())
+    }
+}
+
+impl<T> PointMap<T> for SparseGrid<T> {
+    fn get_point(&self, p: IPoint2) -> Option<&T> {
+        self.get(p)
+    }
+
+    fn point_bounds(&self) -> Option<(IPoint2, IPoint2)> {
+        self.bounds()
+    }
+}
+
+impl<T> PointMap<T> for Grid<T> {
+    fn get_point(&self, p: IPoint2) -> Option<&T> {
+        self.get(p)
+    }
+
+    fn point_bounds(&self) -> Option<(IPoint2, IPoint2)> {
+        self.bounds()
+    }
+}
+
+/// Renders point maps to text, sizing the output to fit the points.
+#[derive(Debug, Clone)]
+pub struct Renderer<P> {
+    palette: P,
+    blank: Glyph,
+    axes: bool,
+    origin: Option<Glyph>,
+    overlay: HashMap<IPoint2, Glyph>,
+    ansi: bool,
+}
+
+impl<P> Renderer<P> {
+    pub fn new(palette: P) -> Self {
+        Self {
+            palette,
+            blank: ' '.into(),
+            axes: false,
+            origin: None,
+            overlay: HashMap::new(),
+            ansi: false,
+        }
+    }
+
+    /// How to draw points with no value, defaults to a space.
+    pub fn blank(mut self, glyph: impl Into<Glyph>) -> Self {
+        self.blank = glyph.into();
+        self
+    }
+
+    /// Draws the x and y axes through empty space.
+    pub fn axes(mut self) -> Self {
+        self.axes = true;
+        self
+    }
+
+    /// Marks the origin, drawn over any value there.
+    pub fn origin(mut self, glyph: impl Into<Glyph>) -> Self {
+        self.origin = Some(glyph.into());
+        self
+    }
+
+    /// Highlights points, drawn over everything else. Later highlights take
+    /// precedence.
+    pub fn highlight<I>(mut self, points: I, glyph: impl Into<Glyph>) -> Self
+    where
+        I: IntoIterator<Item = IPoint2>,
+    {
+        let glyph = glyph.into();
+        self.overlay.extend(points.into_iter().map(|p| (p, glyph)));
+        self
+    }
+
+    /// Wraps coloured glyphs in ANSI escape codes.
+    pub fn ansi(mut self) -> Self {
+        self.ansi = true;
+        self
+    }
+
+    fn bounds<T, M: PointMap<T>>(&self, map: &M) -> Option<(IPoint2, IPoint2)> {
+        let mut points: Vec<IPoint2> = self.overlay.keys().cloned().collect();
+        if let Some((min, max)) = map.point_bounds() {
+            points.push(min);
+            points.push(max);
+        }
+        if self.axes || self.origin.is_some() {
+            points.push(IPoint2::default());
+        }
+        grid::bounds(&points)
+    }
+
+    fn glyph<T>(&self, p: IPoint2, val: Option<&T>) -> Glyph
+    where
+        P: Palette<T>,
+    {
+        if let Some(g) = self.overlay.get(&p) {
+            return *g;
+        }
+        if p == IPoint2::default() {
+            if let Some(g) = self.origin {
+                return g;
+            }
+        }
+        match val {
+            Some(v) => self.palette.glyph(v),
+            None if self.axes && p.x == 0 && p.y == 0 => '+'.into(),
+            None if self.axes && p.x == 0 => '|'.into(),
+            None if self.axes && p.y == 0 => '-'.into(),
+            None => self.blank,
+        }
+    }
+
+    pub fn render<T, M>(&self, map: &M) -> String
+    where
+        P: Palette<T>,
+        M: PointMap<T>,
+    {
+        let (min, max) = match self.bounds(map) {
+            Some(b) => b,
+            None => return String::new(),
+        };
+        (min.y..=max.y)
+            .map(|y| {
+                (min.x..=max.x)
+                    .map(|x| {
+                        let p = IPoint2 { x, y };
+                        let glyph = self.glyph(p, map.get_point(p));
+                        match glyph.colour {
+                            Some(c) if self.ansi => {
+                                format!("\x1b[{}m{}\x1b[0m", c.ansi_code(), glyph.ch)
+                            }
+                            _ => glyph.ch.to_string(),
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map() -> HashMap<IPoint2, bool> {
+        vec![
+            (IPoint2 { x: -1, y: -1 }, true),
+            (IPoint2 { x: 1, y: 1 }, false),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn test_render() {
+        let palette = |b: &bool| if *b { '#' } else { '.' };
+        assert_eq!(Renderer::new(palette).render(&map()), "#  \n   \n  .");
+        assert_eq!(
+            Renderer::new(palette).axes().render(&map()),
+            "#| \n-+-\n |."
+        );
+        assert_eq!(
+            Renderer::new(palette)
+                .origin('o')
+                .highlight(vec![IPoint2 { x: 1, y: 1 }, IPoint2 { x: 2, y: 1 }], '*')
+                .blank('_')
+                .render(&map()),
+            "#___\n_o__\n__**"
+        );
+        assert_eq!(
+            Renderer::new(palette).render(&HashMap::<IPoint2, bool>::new()),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_ansi() {
+        let palette = |b: &bool| {
+            if *b {
+                Glyph::new('#', Colour::Red)
+            } else {
+                '.'.into()
+            }
+        };
+        assert_eq!(
+            Renderer::new(palette).ansi().render(&map()),
+            "\x1b[31m#\x1b[0m  \n   \n  ."
+        );
+        assert_eq!(Renderer::new(palette).render(&map()), "#  \n   \n  .");
+    }
+
+    #[test]
+    fn test_display_palette() {
+        let grid =
+            SparseGrid::parse("ab\n c", |c| Ok(if c == ' ' { None } else { Some(c) })).unwrap();
+        assert_eq!(Renderer::new(DisplayPalette).render(&grid), "ab\n c");
+    }
+}
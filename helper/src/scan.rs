@@ -0,0 +1,415 @@
+use anyhow::Result;
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::grid::Grid;
+
+#[doc(hidden)]
+pub use anyhow;
+
+/// An error at a position in the input, lines and columns counting from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new<S: Into<String>>(line: usize, column: usize, message: S) -> Self {
+        Self {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+/// Moves an error from parsing part of an input to its position in the whole
+/// input, the part starting `lines` lines and `columns` columns in. Errors
+/// without a position are placed at the start of the part.
+pub fn offset(err: anyhow::Error, lines: usize, columns: usize) -> anyhow::Error {
+    match err.downcast::<ParseError>() {
+        Ok(mut e) => {
+            if e.line == 1 {
+                e.column += columns;
+            }
+            e.line += lines;
+            e.into()
+        }
+        Err(err) => ParseError::new(lines + 1, columns + 1, format!("{:#}", err)).into(),
+    }
+}
+
+/// A value captured by `scan`, with the column it starts at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capture<'a> {
+    pub text: &'a str,
+    pub column: usize,
+}
+
+impl<'a> Capture<'a> {
+    pub fn parse<T>(self) -> Result<T>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        self.text.parse().map_err(|e| {
+            ParseError::new(
+                1,
+                self.column,
+                format!("invalid value {:?}: {}", self.text, e),
+            )
+            .into()
+        })
+    }
+}
+
+// Matches a literal at `pos`, whitespace in the literal matching any amount
+// of whitespace. Returns the position after the match.
+fn match_literal(literal: &str, input: &str, mut pos: usize) -> Option<usize> {
+    for c in literal.chars() {
+        if c.is_whitespace() {
+            pos += input[pos..].len() - input[pos..].trim_start().len();
+        } else if input[pos..].starts_with(c) {
+            pos += c.len_utf8();
+        } else {
+            return None;
+        }
+    }
+    Some(pos)
+}
+
+fn column(input: &str, pos: usize) -> usize {
+    input[..pos].chars().count() + 1
+}
+
+/// Matches a single line against a pattern of literal text and `{}`
+/// placeholders, returning what each placeholder matched with surrounding
+/// whitespace trimmed. Whitespace in the pattern matches any amount of
+/// whitespace, including none, unless it is all that separates two
+/// placeholders.
+pub fn scan<'a>(pattern: &str, input: &'a str) -> Result<Vec<Capture<'a>>> {
+    let literals: Vec<&str> = pattern.split("{}").collect();
+    let mut pos = match_literal(literals[0], input, 0)
+        .ok_or_else(|| ParseError::new(1, 1, format!("expected {:?}", literals[0].trim_end())))?;
+    let mut captures = vec![];
+    for (index, literal) in literals.iter().enumerate().skip(1) {
+        if literal.is_empty() && index < literals.len() - 1 {
+            return Err(ParseError::new(
+                1,
+                column(input, pos),
+                format!("pattern {:?} has adjacent placeholders", pattern),
+            )
+            .into());
+        }
+        // Placeholders take everything up to the next literal
+        let (end, next) = if literal.is_empty() {
+            (input.len(), input.len())
+        } else {
+            input[pos..]
+                .char_indices()
+                .map(|(i, _)| pos + i)
+                .chain(Some(input.len()))
+                .find_map(|end| {
+                    match_literal(literal, input, end)
+                        .filter(|next| *next > end || !literal.trim().is_empty())
+                        .map(|next| (end, next))
+                })
+                .ok_or_else(|| {
+                    ParseError::new(1, column(input, pos), format!("expected {:?}", literal))
+                })?
+        };
+        let raw = &input[pos..end];
+        let text = raw.trim();
+        captures.push(Capture {
+            text,
+            column: column(input, pos + raw.len() - raw.trim_start().len()),
+        });
+        pos = next;
+    }
+    let rest = input[pos..].trim();
+    if !rest.is_empty() {
+        return Err(ParseError::new(
+            1,
+            column(input, input.len() - input[pos..].trim_start().len()),
+            format!("unexpected {:?}", rest),
+        )
+        .into());
+    }
+    Ok(captures)
+}
+
+/// Builds a parser for lines matching a pattern, each `{}` being parsed as
+/// the corresponding type. See `scan::scan` for how patterns match.
+///
+/// ```
+/// let parse = helper::scan!("<x={}, y={}>", isize, isize);
+/// assert_eq!(parse("<x=1, y=-2>").unwrap(), (1, -2));
+/// ```
+#[macro_export]
+macro_rules! scan {
+    ($pattern:expr, $($t:ty),+ $(,)?) => {
+        |input: &str| -> $crate::scan::anyhow::Result<($($t,)+)> {
+            let captures = $crate::scan::scan($pattern, input)?;
+            let expected = [$(stringify!($t)),+].len();
+            if captures.len() != expected {
+                return Err($crate::scan::anyhow::anyhow!(
+                    "pattern {:?} has {} placeholders, expected {}",
+                    $pattern,
+                    captures.len(),
+                    expected
+                ));
+            }
+            let mut captures = captures.into_iter();
+            Ok(($(captures.next().unwrap().parse::<$t>()?,)+))
+        }
+    };
+}
+
+/// Parses each line, giving errors the line number.
+pub fn lines<'a, T, F>(input: &'a str, f: F) -> Result<Vec<T>>
+where
+    F: Fn(&'a str) -> Result<T>,
+{
+    input
+        .trim_end()
+        .lines()
+        .enumerate()
+        .map(|(index, line)| f(line).map_err(|e| offset(e, index, 0)))
+        .collect()
+}
+
+/// Parses each item of a single line list, items being trimmed of whitespace.
+pub fn list<'a, T, F>(input: &'a str, sep: char, f: F) -> Result<Vec<T>>
+where
+    F: Fn(&'a str) -> Result<T>,
+{
+    let input = input.trim_end();
+    let mut start = 0;
+    input
+        .split(sep)
+        .map(|item| {
+            let item_start = start + item.len() - item.trim_start().len();
+            start += item.len() + sep.len_utf8();
+            f(item.trim()).map_err(|e| offset(e, 0, column(input, item_start) - 1))
+        })
+        .collect()
+}
+
+/// Parses each item of a comma separated list.
+pub fn comma_list<'a, T, F>(input: &'a str, f: F) -> Result<Vec<T>>
+where
+    F: Fn(&'a str) -> Result<T>,
+{
+    list(input, ',', f)
+}
+
+/// Parses each block of lines, blocks being separated by blank lines.
+pub fn blocks<'a, T, F>(input: &'a str, f: F) -> Result<Vec<T>>
+where
+    F: Fn(&'a str) -> Result<T>,
+{
+    let mut blocks: Vec<(usize, &str)> = vec![];
+    let mut start: Option<(usize, usize)> = None;
+    let mut pos = 0;
+    for (index, line) in input.split('\n').enumerate() {
+        match (line.trim().is_empty(), start) {
+            (true, Some((line_start, byte_start))) => {
+                blocks.push((line_start, input[byte_start..pos].trim_end()));
+                start = None;
+            }
+            (false, None) => start = Some((index, pos)),
+            _ => {}
+        }
+        pos += line.len() + 1;
+    }
+    if let Some((line_start, byte_start)) = start {
+        blocks.push((line_start, input[byte_start..].trim_end()));
+    }
+    blocks
+        .into_iter()
+        .map(|(line_start, block)| f(block).map_err(|e| offset(e, line_start, 0)))
+        .collect()
+}
+
+/// Splits a key and value on the first `sep`, trimming both.
+pub fn pair<'a>(input: &'a str, sep: &str) -> Result<(&'a str, &'a str)> {
+    match input.find(sep) {
+        Some(index) => Ok((input[..index].trim(), input[index + sep.len()..].trim())),
+        None => Err(ParseError::new(
+            1,
+            column(input, input.trim_end().len()),
+            format!("expected {:?}", sep),
+        )
+        .into()),
+    }
+}
+
+/// Parses a key and value split on the first `sep`.
+pub fn parse_pair<'a, K, V, FK, FV>(input: &'a str, sep: &str, fk: FK, fv: FV) -> Result<(K, V)>
+where
+    FK: Fn(&'a str) -> Result<K>,
+    FV: Fn(&'a str) -> Result<V>,
+{
+    let (k, v) = pair(input, sep)?;
+    let v_start = input.len()
+        - input[input.find(sep).unwrap() + sep.len()..]
+            .trim_start()
+            .len();
+    let k_start = input.len() - input.trim_start().len();
+    Ok((
+        fk(k).map_err(|e| offset(e, 0, column(input, k_start) - 1))?,
+        fv(v).map_err(|e| offset(e, 0, column(input, v_start) - 1))?,
+    ))
+}
+
+/// Parses a single line list of key value pairs, eg. `a: 1, b: 2`.
+pub fn key_values<'a>(
+    input: &'a str,
+    item_sep: char,
+    kv_sep: &str,
+) -> Result<Vec<(&'a str, &'a str)>> {
+    list(input, item_sep, |item| pair(item, kv_sep))
+}
+
+/// Parses a grid of chars, giving errors the line and column.
+pub fn char_grid<T, F>(input: &str, f: F) -> Result<Grid<T>>
+where
+    F: Fn(char) -> Result<T>,
+{
+    let mut rows: Vec<Vec<T>> = vec![];
+    for (y, line) in input.trim_end().lines().enumerate() {
+        let row = line
+            .chars()
+            .enumerate()
+            .map(|(x, c)| f(c).map_err(|e| offset(e, y, x)))
+            .collect::<Result<Vec<T>>>()?;
+        if let Some(first) = rows.first() {
+            if row.len() != first.len() {
+                return Err(ParseError::new(
+                    y + 1,
+                    row.len() + 1,
+                    format!("expected {} columns, got {}", first.len(), row.len()),
+                )
+                .into());
+            }
+        }
+        rows.push(row);
+    }
+    Grid::from_rows(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    fn position(err: anyhow::Error) -> (usize, usize) {
+        let e = err.downcast::<ParseError>().unwrap();
+        (e.line, e.column)
+    }
+
+    #[test]
+    fn test_scan() -> Result<()> {
+        let parse = crate::scan!("<x={}, y={}, z={}>", isize, isize, isize);
+        assert_eq!(parse("<x=-2, y=0,z=3>")?, (-2, 0, 3));
+        assert_eq!(parse("<x= -8, y=-10, z=  0>")?, (-8, -10, 0));
+        assert_eq!(position(parse("<x=1, y=a, z=3>").unwrap_err()), (1, 9));
+        assert_eq!(position(parse("<x=1, y=2>").unwrap_err()), (1, 9));
+        assert_eq!(position(parse("<x=1, y=2, z=3> !").unwrap_err()), (1, 17));
+
+        let parse = crate::scan!("{} => {}", String, String);
+        assert_eq!(
+            parse("7 A, 1 B => 1 C")?,
+            ("7 A, 1 B".to_string(), "1 C".to_string())
+        );
+        assert_eq!(
+            crate::scan!("{} {}", usize, String)("10  ORE")?,
+            (10, "ORE".to_string())
+        );
+        assert!(crate::scan!("{}{}", usize, usize)("12").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_lines() -> Result<()> {
+        let parse = crate::scan!("{}x{}x{}", usize, usize, usize);
+        assert_eq!(
+            lines("2x3x4\n1x1x10\n", parse)?,
+            vec![(2, 3, 4), (1, 1, 10)]
+        );
+        let err = lines("2x3x4\n1xbx10", parse).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 3: invalid value \"b\": invalid digit found in string"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_list() -> Result<()> {
+        let parse = |s: &str| Ok(s.parse::<isize>()?);
+        assert_eq!(comma_list("1,-2, 3\n", parse)?, vec![1, -2, 3]);
+        assert_eq!(list("2x3x4", 'x', parse)?, vec![2, 3, 4]);
+        assert_eq!(position(comma_list("1,2, x", parse).unwrap_err()), (1, 6));
+        let nested = lines("1,2\n3,4,x", |l| comma_list(l, parse)).unwrap_err();
+        assert_eq!(position(nested), (2, 5));
+        Ok(())
+    }
+
+    #[test]
+    fn test_blocks() -> Result<()> {
+        let input = "a\nb\n\n\nc\n";
+        assert_eq!(blocks(input, |b| Ok(b.to_string()))?, vec!["a\nb", "c"]);
+        let err = blocks(input, |b| {
+            lines(b, |l| if l == "c" { Err(anyhow!("bad")) } else { Ok(l) })
+        });
+        assert_eq!(position(err.unwrap_err()), (5, 1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_pairs() -> Result<()> {
+        assert_eq!(pair("COM)B", ")")?, ("COM", "B"));
+        assert_eq!(
+            key_values("a: 1, b: 2", ',', ":")?,
+            vec![("a", "1"), ("b", "2")]
+        );
+        assert_eq!(position(pair("COMB", ")").unwrap_err()), (1, 5));
+        let parse = |s: &str| Ok(s.parse::<usize>()?);
+        assert_eq!(parse_pair("x = 5", "=", Ok, parse)?, ("x", 5));
+        assert_eq!(
+            position(parse_pair("x = y", "=", Ok, parse).unwrap_err()),
+            (1, 5)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_char_grid() -> Result<()> {
+        let parse = |c| match c {
+            '#' => Ok(true),
+            '.' => Ok(false),
+            _ => Err(anyhow!("invalid char {}", c)),
+        };
+        assert_eq!(char_grid("#.\n.#\n", parse)?.width(), 2);
+        assert_eq!(position(char_grid("#.\n.x", parse).unwrap_err()), (2, 2));
+        assert_eq!(position(char_grid("#.\n.", parse).unwrap_err()), (2, 2));
+        Ok(())
+    }
+}
@@ -0,0 +1,288 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+use crate::grid::ORTHOGONAL;
+use crate::point::IPoint2;
+
+/// Anything which can be used as an edge cost, `Default` being zero.
+pub trait Cost: Copy + Ord + Add<Output = Self> + Default {}
+
+impl<T> Cost for T where T: Copy + Ord + Add<Output = T> + Default {}
+
+/// The outcome of a search, holding the best known cost to every visited node
+/// and the links needed to reconstruct paths back to the start.
+#[derive(Debug, Clone)]
+pub struct Search<N, C>
+where
+    N: Eq + Hash,
+{
+    costs: HashMap<N, C>,
+    prev: HashMap<N, N>,
+    reached: Option<N>,
+}
+
+impl<N, C> Search<N, C>
+where
+    N: Clone + Eq + Hash,
+    C: Copy,
+{
+    fn new() -> Self {
+        Self {
+            costs: HashMap::new(),
+            prev: HashMap::new(),
+            reached: None,
+        }
+    }
+
+    /// The first target reached, if any.
+    pub fn reached(&self) -> Option<&N> {
+        self.reached.as_ref()
+    }
+
+    pub fn cost(&self, node: &N) -> Option<C> {
+        self.costs.get(node).cloned()
+    }
+
+    pub fn costs(&self) -> &HashMap<N, C> {
+        &self.costs
+    }
+
+    pub fn into_costs(self) -> HashMap<N, C> {
+        self.costs
+    }
+
+    /// The path from whichever start was closest to `node`, including both
+    /// ends.
+    pub fn path(&self, node: &N) -> Option<Vec<N>> {
+        if !self.costs.contains_key(node) {
+            return None;
+        }
+        let mut path = vec![node.clone()];
+        let mut at = node;
+        while let Some(p) = self.prev.get(at) {
+            path.push(p.clone());
+            at = p;
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    pub fn reached_path(&self) -> Option<Vec<N>> {
+        self.reached.as_ref().and_then(|r| self.path(r))
+    }
+}
+
+/// Breadth first search from all of `starts`, stopping at the first node
+/// matching `is_target`. Use `|_| false` as the target to visit everything
+/// reachable.
+pub fn bfs<N, S, FN, I, FT>(starts: S, mut successors: FN, is_target: FT) -> Search<N, usize>
+where
+    N: Clone + Eq + Hash,
+    S: IntoIterator<Item = N>,
+    FN: FnMut(&N) -> I,
+    I: IntoIterator<Item = N>,
+    FT: Fn(&N) -> bool,
+{
+    let mut search = Search::new();
+    let mut queue: VecDeque<N> = VecDeque::new();
+    for s in starts {
+        if !search.costs.contains_key(&s) {
+            search.costs.insert(s.clone(), 0);
+            queue.push_back(s);
+        }
+    }
+    while let Some(node) = queue.pop_front() {
+        if is_target(&node) {
+            search.reached = Some(node);
+            break;
+        }
+        let next_cost = search.costs[&node] + 1;
+        for next in successors(&node) {
+            if !search.costs.contains_key(&next) {
+                search.costs.insert(next.clone(), next_cost);
+                search.prev.insert(next.clone(), node.clone());
+                queue.push_back(next);
+            }
+        }
+    }
+    search
+}
+
+/// Dijkstra's algorithm from all of `starts`, stopping at the first node
+/// matching `is_target`.
+pub fn dijkstra<N, C, S, FN, I, FT>(starts: S, successors: FN, is_target: FT) -> Search<N, C>
+where
+    N: Clone + Eq + Hash,
+    C: Cost,
+    S: IntoIterator<Item = N>,
+    FN: FnMut(&N) -> I,
+    I: IntoIterator<Item = (N, C)>,
+    FT: Fn(&N) -> bool,
+{
+    astar(starts, successors, |_| C::default(), is_target)
+}
+
+/// A* from all of `starts`, stopping at the first node matching `is_target`.
+/// The heuristic must never overestimate the remaining cost for the reached
+/// path to be optimal.
+pub fn astar<N, C, S, FN, I, FH, FT>(
+    starts: S,
+    mut successors: FN,
+    heuristic: FH,
+    is_target: FT,
+) -> Search<N, C>
+where
+    N: Clone + Eq + Hash,
+    C: Cost,
+    S: IntoIterator<Item = N>,
+    FN: FnMut(&N) -> I,
+    I: IntoIterator<Item = (N, C)>,
+    FH: Fn(&N) -> C,
+    FT: Fn(&N) -> bool,
+{
+    let mut search = Search::new();
+    // Nodes are stored separately so the queue doesn't need them to be Ord
+    let mut nodes: Vec<N> = vec![];
+    // Use Reverse to create a min-heap instead of a max-heap
+    let mut queue: BinaryHeap<Reverse<(C, C, usize)>> = BinaryHeap::new();
+    for s in starts {
+        if !search.costs.contains_key(&s) {
+            search.costs.insert(s.clone(), C::default());
+            queue.push(Reverse((heuristic(&s), C::default(), nodes.len())));
+            nodes.push(s);
+        }
+    }
+    while let Some(Reverse((_, cost, index))) = queue.pop() {
+        let node = nodes[index].clone();
+        if search.costs[&node] < cost {
+            // A cheaper route to this node has already been handled
+            continue;
+        }
+        if is_target(&node) {
+            search.reached = Some(node);
+            break;
+        }
+        for (next, step) in successors(&node) {
+            let next_cost = cost + step;
+            if search.costs.get(&next).is_none_or(|c| next_cost < *c) {
+                search.costs.insert(next.clone(), next_cost);
+                search.prev.insert(next.clone(), node.clone());
+                queue.push(Reverse((
+                    next_cost + heuristic(&next),
+                    next_cost,
+                    nodes.len(),
+                )));
+                nodes.push(next);
+            }
+        }
+    }
+    search
+}
+
+/// The orthogonal neighbours of `p` which are `passable`.
+pub fn grid_successors<F>(p: IPoint2, passable: F) -> impl Iterator<Item = IPoint2>
+where
+    F: Fn(IPoint2) -> bool,
+{
+    ORTHOGONAL
+        .iter()
+        .map(move |o| p + *o)
+        .filter(move |n| passable(*n))
+}
+
+/// The shortest orthogonal path between two points, including both ends.
+pub fn grid_path<F>(from: IPoint2, to: IPoint2, passable: F) -> Option<Vec<IPoint2>>
+where
+    F: Fn(IPoint2) -> bool,
+{
+    astar(
+        vec![from],
+        |p| grid_successors(*p, &passable).map(|n| (n, 1)),
+        |p| (to - *p).manhattan(),
+        |p| *p == to,
+    )
+    .reached_path()
+}
+
+/// The orthogonal step distance to every point reachable from `starts`.
+pub fn grid_distances<S, F>(starts: S, passable: F) -> HashMap<IPoint2, usize>
+where
+    S: IntoIterator<Item = IPoint2>,
+    F: Fn(IPoint2) -> bool,
+{
+    bfs(starts, |p| grid_successors(*p, &passable), |_| false).into_costs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Grid;
+    use anyhow::Result;
+
+    // 0 -1-> 1 -1-> 2 -1-> 3
+    //  \-------5--------/
+    fn successors(n: &u32) -> Vec<(u32, u32)> {
+        match n {
+            0 => vec![(1, 1), (3, 5)],
+            1 => vec![(2, 1)],
+            2 => vec![(3, 1)],
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn test_bfs() {
+        let search = bfs(
+            vec![0],
+            |n| successors(n).into_iter().map(|(n, _)| n),
+            |n| *n == 3,
+        );
+        assert_eq!(search.reached(), Some(&3));
+        assert_eq!(search.reached_path(), Some(vec![0, 3]));
+        assert_eq!(search.cost(&3), Some(1));
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        let search = dijkstra(vec![0], successors, |n| *n == 3);
+        assert_eq!(search.reached_path(), Some(vec![0, 1, 2, 3]));
+        assert_eq!(search.cost(&3), Some(3));
+
+        let search = dijkstra(vec![0], successors, |_| false);
+        assert_eq!(search.costs().len(), 4);
+        assert_eq!(search.path(&2), Some(vec![0, 1, 2]));
+        assert_eq!(search.reached(), None);
+    }
+
+    #[test]
+    fn test_multi_source_and_target() {
+        let search = dijkstra(vec![0, 2], successors, |n| *n == 1 || *n == 3);
+        assert_eq!(search.reached(), Some(&1));
+        assert_eq!(search.cost(&1), Some(1));
+        assert_eq!(search.cost(&3), Some(1));
+        assert_eq!(search.path(&3), Some(vec![2, 3]));
+    }
+
+    #[test]
+    fn test_grid_path() -> Result<()> {
+        let maze = Grid::parse("#######\n#.....#\n#.###.#\n#...#.#\n#######", |c| {
+            Ok(c == '.')
+        })?;
+        let passable = |p: IPoint2| maze.get(p).cloned().unwrap_or(false);
+        let path = grid_path(IPoint2 { x: 3, y: 3 }, IPoint2 { x: 5, y: 3 }, passable).unwrap();
+        assert_eq!(path.len(), 11);
+        assert_eq!(path[0], IPoint2 { x: 3, y: 3 });
+        assert_eq!(path[10], IPoint2 { x: 5, y: 3 });
+        assert_eq!(
+            grid_path(IPoint2 { x: 1, y: 1 }, IPoint2 { x: 0, y: 0 }, passable),
+            None
+        );
+
+        let dists = grid_distances(vec![IPoint2 { x: 1, y: 1 }], passable);
+        assert_eq!(dists.len(), 11);
+        assert_eq!(dists.values().max(), Some(&6));
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+use anyhow::{Context, Result};
+
+use helper::scan::{self, ParseError};
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Identifies the answer for one part of a day with a named input.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Key {
+    pub year: usize,
+    pub day: usize,
+    pub part: char,
+    pub input: String,
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{:02}/{}/{}",
+            self.year, self.day, self.part, self.input
+        )
+    }
+}
+
+/// Accepted answers, stored one per line as `year/day/part/input = answer`.
+/// Blank lines and lines starting with `#` are ignored.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Answers(BTreeMap<Key, String>);
+
+impl Answers {
+    pub fn parse(input: &str) -> Result<Self> {
+        let parse = helper::scan!("{}/{}/{}/{} = {}", usize, usize, char, String, String);
+        let mut answers = BTreeMap::new();
+        for (index, line) in input.lines().enumerate() {
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                continue;
+            }
+            let (year, day, part, input, answer) =
+                parse(line).map_err(|e| scan::offset(e, index, 0))?;
+            let key = Key {
+                year,
+                day,
+                part,
+                input,
+            };
+            if answers.insert(key.clone(), answer).is_some() {
+                return Err(
+                    ParseError::new(index + 1, 1, format!("duplicate answer for {}", key)).into(),
+                );
+            }
+        }
+        Ok(Answers(answers))
+    }
+
+    /// Loads answers from a file, which is empty if it doesn't exist yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(input) => {
+                Self::parse(&input).with_context(|| format!("failed parsing {}", path.display()))
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("failed reading {}", path.display())),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.to_string())
+            .with_context(|| format!("failed writing {}", path.display()))
+    }
+
+    pub fn get(&self, key: &Key) -> Option<&str> {
+        self.0.get(key).map(|a| a.as_str())
+    }
+
+    pub fn insert(&mut self, key: Key, answer: String) -> Option<String> {
+        self.0.insert(key, answer)
+    }
+}
+
+impl fmt::Display for Answers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# year/day/part/input = answer")?;
+        for (key, answer) in &self.0 {
+            writeln!(f, "{} = {}", key, answer)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() -> Result<()> {
+        let answers =
+            Answers::parse("# comment\n\n2019/04/b/input = 1145\n2019/08/b/alice = LRFKU\n")?;
+        let key = Key {
+            year: 2019,
+            day: 8,
+            part: 'b',
+            input: "alice".to_string(),
+        };
+        assert_eq!(answers.get(&key), Some("LRFKU"));
+        assert_eq!(Answers::parse(&answers.to_string())?, answers);
+
+        let err = Answers::parse("2019/04/b/input = 1\n2019/4/b/input = 2").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 1: duplicate answer for 2019/04/b/input"
+        );
+        assert!(Answers::parse("2019/04/input = 1").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,283 @@
+use anyhow::{anyhow, Context, Result};
+
+use helper::input::{Inputs, DEFAULT_NAME};
+
+use std::env;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Stdio};
+use std::time::{Duration, Instant};
+
+mod answers;
+
+use answers::{Answers, Key};
+
+const ANSWERS_FILE: &str = "answers.txt";
+
+const USAGE: &str = "usage:
+    runner input <year> <day> [name]         print the path of an input
+    runner inputs <year> <day>               list the named inputs for a day
+    runner store <year> <day> <name> <file>  copy an input into the cache
+    runner run <year> <day> [a|b] [name]     run a day with an input
+    runner check [year] [day] [--update]     check every input against answers.txt,
+                                             --update records new and changed answers";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Solution {
+    year: usize,
+    day: usize,
+    part: char,
+}
+
+impl Solution {
+    fn package(&self) -> String {
+        format!("aoc{}day{:02}{}", self.year, self.day, self.part)
+    }
+
+    fn build(&self, root: &Path) -> Result<()> {
+        build(root, &["--package", &self.package()])
+    }
+
+    // Runs the built solution with an input on stdin, returning what it
+    // printed
+    fn run(&self, root: &Path, input: &Path) -> Result<String> {
+        let output = Command::new(root.join("target/release").join(self.package()))
+            .current_dir(
+                root.join(self.year.to_string())
+                    .join(format!("day{:02}{}", self.day, self.part)),
+            )
+            .stdin(Stdio::from(File::open(input)?))
+            .stderr(Stdio::inherit())
+            .output()
+            .with_context(|| format!("failed running {}", self.package()))?;
+        if !output.status.success() {
+            return Err(anyhow!("{} exited with {}", self.package(), output.status));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+fn build(root: &Path, args: &[&str]) -> Result<()> {
+    let status = Command::new("cargo")
+        .args(["build", "--release", "--quiet"])
+        .args(args)
+        .current_dir(root)
+        .status()
+        .context("failed running cargo")?;
+    if !status.success() {
+        return Err(anyhow!("failed building {}", args.join(" ")));
+    }
+    Ok(())
+}
+
+/// Finds all solutions, which live in `<year>/day<DD><part>` directories.
+fn solutions(root: &Path) -> Result<Vec<Solution>> {
+    let mut solutions = vec![];
+    for year_entry in fs::read_dir(root)? {
+        let year_entry = year_entry?;
+        let year: usize = match year_entry.file_name().to_string_lossy().parse() {
+            Ok(y) => y,
+            Err(_) => continue,
+        };
+        for day_entry in fs::read_dir(year_entry.path())? {
+            let name = day_entry?.file_name().to_string_lossy().into_owned();
+            if !name.starts_with("day") || name.len() != 6 {
+                continue;
+            }
+            if let (Ok(day), Some(part)) = (name[3..5].parse(), name.chars().nth(5)) {
+                solutions.push(Solution { year, day, part });
+            }
+        }
+    }
+    solutions.sort_by_key(|s| (s.year, s.day, s.part));
+    Ok(solutions)
+}
+
+fn arg<T: std::str::FromStr>(args: &[String], index: usize, name: &str) -> Result<T> {
+    args.get(index)
+        .ok_or_else(|| anyhow!("missing {}\n{}", name, USAGE))?
+        .parse()
+        .map_err(|_| anyhow!("invalid {}: {}", name, args[index]))
+}
+
+fn main() -> Result<()> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .ok_or_else(|| anyhow!("runner must be in the repository"))?
+        .to_path_buf();
+    let inputs = Inputs::from_env(&root);
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match args.first().map(|a| a.as_str()) {
+        Some("input") => {
+            let name = args.get(3).map(|n| n.as_str()).unwrap_or(DEFAULT_NAME);
+            let path = inputs.find(arg(&args, 1, "year")?, arg(&args, 2, "day")?, name)?;
+            println!("{}", path.display());
+        }
+        Some("inputs") => {
+            for name in inputs.names(arg(&args, 1, "year")?, arg(&args, 2, "day")?)? {
+                println!("{}", name);
+            }
+        }
+        Some("store") => {
+            let from: PathBuf = arg(&args, 4, "file")?;
+            let name: String = arg(&args, 3, "name")?;
+            let to = inputs.store(arg(&args, 1, "year")?, arg(&args, 2, "day")?, &name, from)?;
+            println!("{}", to.display());
+        }
+        Some("run") => {
+            let year = arg(&args, 1, "year")?;
+            let day = arg(&args, 2, "day")?;
+            // The part and name are both optional
+            let (part, name) = match args.get(3).map(|a| a.as_str()) {
+                Some(p @ "a") | Some(p @ "b") => (p.chars().next(), args.get(4)),
+                _ => (None, args.get(3)),
+            };
+            let name = name.map(|n| n.as_str()).unwrap_or(DEFAULT_NAME);
+            let input = inputs.find(year, day, name)?;
+            let to_run: Vec<Solution> = solutions(&root)?
+                .into_iter()
+                .filter(|s| {
+                    s.year == year && s.day == day && part.map(|p| p == s.part).unwrap_or(true)
+                })
+                .collect();
+            if to_run.is_empty() {
+                return Err(anyhow!("no solutions for {} day {}", year, day));
+            }
+            for solution in to_run {
+                println!("== {} day {}{} ({}) ==", year, day, solution.part, name);
+                solution.build(&root)?;
+                print!("{}", solution.run(&root, &input)?);
+            }
+        }
+        Some("check") => {
+            let update = args.iter().any(|a| a == "--update");
+            let filter: Vec<usize> = args[1..]
+                .iter()
+                .filter(|a| !a.starts_with("--"))
+                .map(|a| a.parse().map_err(|_| anyhow!("invalid year or day: {}", a)))
+                .collect::<Result<_>>()?;
+            if !check(&root, &inputs, &filter, update)? {
+                process::exit(1);
+            }
+        }
+        _ => return Err(anyhow!("{}", USAGE)),
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Status {
+    Pass,
+    New,
+    Changed(String),
+    Fail(String),
+}
+
+/// The answer is the last line a solution prints, as many print working or a
+/// drawing first.
+fn answer(output: &str) -> String {
+    output
+        .lines()
+        .rev()
+        .map(|l| l.trim())
+        .find(|l| !l.is_empty())
+        .unwrap_or("")
+        .to_string()
+}
+
+fn check_one(
+    root: &Path,
+    solution: &Solution,
+    input: &Path,
+    expected: Option<&str>,
+) -> (Status, String, Duration) {
+    let start = Instant::now();
+    let result = solution.run(root, input);
+    let elapsed = start.elapsed();
+    match result {
+        Ok(output) => {
+            let got = answer(&output);
+            let status = match expected {
+                Some(e) if e == got => Status::Pass,
+                Some(e) => Status::Changed(e.to_string()),
+                None => Status::New,
+            };
+            (status, got, elapsed)
+        }
+        Err(e) => (Status::Fail(format!("{:#}", e)), String::new(), elapsed),
+    }
+}
+
+/// Runs every solution, or those for a year and day, with each of its inputs
+/// and compares them with the recorded answers. Returns whether there were
+/// no regressions.
+fn check(root: &Path, inputs: &Inputs, filter: &[usize], update: bool) -> Result<bool> {
+    let answers_path = root.join(ANSWERS_FILE);
+    let mut answers = Answers::load(&answers_path)?;
+    build(root, &["--workspace"])?;
+
+    let (mut passed, mut new, mut changed, mut failed) = (0, 0, 0, 0);
+    for solution in solutions(root)? {
+        if filter.first().map(|y| *y != solution.year).unwrap_or(false)
+            || filter.get(1).map(|d| *d != solution.day).unwrap_or(false)
+        {
+            continue;
+        }
+        for name in inputs.names(solution.year, solution.day)? {
+            let key = Key {
+                year: solution.year,
+                day: solution.day,
+                part: solution.part,
+                input: name.clone(),
+            };
+            let input = inputs.find(solution.year, solution.day, &name)?;
+            let (status, got, elapsed) = check_one(root, &solution, &input, answers.get(&key));
+            match &status {
+                Status::Pass => {
+                    passed += 1;
+                    println!("PASS    {} = {} ({:.2?})", key, got, elapsed);
+                }
+                Status::New => {
+                    new += 1;
+                    println!("NEW     {} = {} ({:.2?})", key, got, elapsed);
+                }
+                Status::Changed(expected) => {
+                    changed += 1;
+                    println!(
+                        "CHANGED {} = {}, expected {} ({:.2?})",
+                        key, got, expected, elapsed
+                    );
+                }
+                Status::Fail(err) => {
+                    failed += 1;
+                    println!("FAIL    {}: {}", key, err);
+                }
+            }
+            if update && (status == Status::New || matches!(status, Status::Changed(_))) {
+                answers.insert(key, got);
+            }
+        }
+    }
+    println!(
+        "{} passed, {} new, {} changed, {} failed",
+        passed, new, changed, failed
+    );
+    if update {
+        answers.save(&answers_path)?;
+        return Ok(failed == 0);
+    }
+    Ok(changed == 0 && failed == 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_answer() {
+        assert_eq!(answer("#  #\n## #\nAB\n\n"), "AB");
+        assert_eq!(answer("Game over\n17159\n"), "17159");
+        assert_eq!(answer(""), "");
+    }
+}